@@ -1,5 +1,7 @@
 use bevy::prelude::*;
 
+use crate::game::GameSpeed;
+
 pub struct AnimatedSpritePlugin;
 
 impl Plugin for AnimatedSpritePlugin {
@@ -59,10 +61,11 @@ fn setup_animated_sprite(
 
 fn animate_sprite(
     time: Res<Time>,
+    speed: Res<GameSpeed>,
     mut query: Query<(&mut AnimationTimer, &mut Sprite, &AnimatedSprite)>,
 ) {
     for (mut timer, mut sprite, animated_sprite) in &mut query {
-        timer.tick(time.delta());
+        timer.tick(time.delta().mul_f32(speed.0));
 
         if timer.is_finished() && let Some(atlas) = &mut sprite.texture_atlas {
             atlas.index = if atlas.index == animated_sprite.frames as usize - 1 {
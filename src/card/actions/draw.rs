@@ -0,0 +1,45 @@
+use bevy::prelude::*;
+
+use crate::combat_log::CombatLogEntry;
+use crate::card::{CardsView, ExtraCards, MAX_HAND_SIZE};
+
+use super::{ActionKind, ActionSource, CardAction};
+
+/// Draw extra cards into the next hand, one for every `per_point` points.
+#[derive(Component)]
+pub struct ActionDrawCards {
+    pub per_point: u64,
+}
+
+#[derive(Message)]
+pub struct DrawCards {
+    pub count: u64,
+    pub source: ActionSource,
+}
+
+impl CardAction for ActionDrawCards {
+    type Output = DrawCards;
+
+    fn kind() -> ActionKind {
+        ActionKind::Draw
+    }
+
+    fn resolve(&self, source: ActionSource) -> Option<DrawCards> {
+        let count = source.points / self.per_point.max(1);
+        (count > 0).then_some(DrawCards {
+            count,
+            source,
+        })
+    }
+}
+
+pub fn draw_cards(
+    mut extra: Single<&mut ExtraCards, With<CardsView>>,
+    mut reader: MessageReader<DrawCards>,
+    mut log: MessageWriter<CombatLogEntry>,
+) {
+    for draw in reader.read() {
+        extra.0 = (extra.0 + draw.count as usize).min(MAX_HAND_SIZE);
+        log.write(CombatLogEntry::new(&draw.source, "draw", draw.count));
+    }
+}
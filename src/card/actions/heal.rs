@@ -0,0 +1,42 @@
+use bevy::prelude::*;
+
+use crate::{combat_log::CombatLogEntry, healthbar::{Health, MaxHealth}, player::Player};
+
+use super::{ActionKind, ActionSource, CardAction};
+
+/// Heal the player for every point.
+#[derive(Component)]
+pub struct ActionHeal(pub u64);
+
+#[derive(Message)]
+pub struct HealPlayer {
+    pub amount: u64,
+    pub source: ActionSource,
+}
+
+impl CardAction for ActionHeal {
+    type Output = HealPlayer;
+
+    fn kind() -> ActionKind {
+        ActionKind::Heal
+    }
+
+    fn resolve(&self, source: ActionSource) -> Option<HealPlayer> {
+        (source.points > 0).then(|| HealPlayer {
+            amount: source.points * self.0,
+            source,
+        })
+    }
+}
+
+pub fn heal_player(
+    mut player: Single<(&mut Health, &MaxHealth), With<Player>>,
+    mut reader: MessageReader<HealPlayer>,
+    mut log: MessageWriter<CombatLogEntry>,
+) {
+    let (ref mut health, max_health) = *player;
+    for heal in reader.read() {
+        health.0 = (health.0 + heal.amount).min(max_health.0);
+        log.write(CombatLogEntry::new(&heal.source, "heal", heal.amount).with_health(health.0));
+    }
+}
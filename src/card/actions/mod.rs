@@ -1,109 +1,357 @@
+mod draw;
+mod heal;
+mod moves;
+mod recolor;
+mod shield;
+mod status;
+mod targeting;
+
 use bevy::prelude::*;
-use bevy_write_after::{self, MessagePool, GlobalMessagePool};
+use bevy_rand::{global::GlobalRng, prelude::WyRand};
+
+use crate::{combat_log::CombatLogEntry, damage::{calculate_hit, roll_damage, Armor, HitTarget, CriticalHit, DamageDealt, DamageType, DamageVariance, Resistances}, enemy::Enemy, player::Player, grid::{GridTile, GridTileByIndex, GridTileColor, Index}, healthbar::{Health, Shield}, layout::GameView, status::{StatusEffect, StatusEffects}};
 
-use crate::{enemy::Enemy, grid::{GridTile, GridTileByIndex, GridTileColor}, healthbar::Health};
-use crate::score::Score;
+use super::{resolving::PROJECTILE_SECS, tasks::{Task, Tasks}, CardIndex, CardInfo, CardRequirement, CardScoring};
 
-use super::{CardIndex, CardRequirement};
+pub use draw::{ActionDrawCards, DrawCards};
+pub use heal::{ActionHeal, HealPlayer};
+pub use moves::{ActionGainMoves, GainMoves};
+pub use recolor::{ActionRecolor, RecolorTiles};
+pub use shield::{ActionShield, GainShield};
+pub use status::{ActionApplyStatus, ApplyStatus};
+pub use targeting::{CardTargets, Targeting};
 
 #[derive(SystemSet, Clone, PartialEq, Eq, Debug, Hash)]
 enum ActionSet {
     Main,
-    Finish,
 }
 
+/// Delay before the first action of a card resolves.
+const CARD_DELAY_SECS: f32 = 0.75;
+
+/// Delay between two actions of the same card.
+const ACTION_STEP_SECS: f32 = 0.25;
+
 pub struct ActionPlugin;
 
 impl Plugin for ActionPlugin {
     fn build(&self, app: &mut App) {
         app
-            .configure_sets(Update, (
-                ActionSet::Main,
-                ActionSet::Finish.after(ActionSet::Main)
-            ))
-            .add_message::<ActionMessagePoolEmpty>()
+            .configure_sets(Update, ActionSet::Main)
             .add_message::<DamageEnemy>()
+            .add_message::<DamageDealt>()
+            .add_message::<HealPlayer>()
+            .add_message::<GainShield>()
+            .add_message::<DrawCards>()
+            .add_message::<GainMoves>()
+            .add_message::<RecolorTiles>()
+            .add_message::<ApplyStatus>()
             .add_message::<ExecuteActions>()
-            .add_message::<FinishedExecution>()
-            .add_systems(Startup, setup_message_pool)
-            .add_systems(Update, action_combine.in_set(ActionSet::Main).run_if(on_message::<ExecuteActions>))
+            .add_message::<ActionResolved>()
+            .add_systems(Update, targeting::choose_card_targets.before(ActionSet::Main).run_if(on_message::<ExecuteActions>))
+            .add_systems(Update, action_system::<ActionCombine>.in_set(ActionSet::Main).run_if(on_message::<ExecuteActions>))
+            .add_systems(Update, action_system::<ActionHeal>.in_set(ActionSet::Main).run_if(on_message::<ExecuteActions>))
+            .add_systems(Update, action_system::<ActionShield>.in_set(ActionSet::Main).run_if(on_message::<ExecuteActions>))
+            .add_systems(Update, action_system::<ActionDrawCards>.in_set(ActionSet::Main).run_if(on_message::<ExecuteActions>))
+            .add_systems(Update, action_system::<ActionGainMoves>.in_set(ActionSet::Main).run_if(on_message::<ExecuteActions>))
+            .add_systems(Update, action_system::<ActionRecolor>.in_set(ActionSet::Main).run_if(on_message::<ExecuteActions>))
+            .add_systems(Update, action_system::<ActionApplyStatus>.in_set(ActionSet::Main).run_if(on_message::<ExecuteActions>))
             .add_systems(Update, damage_enemy.in_set(ActionSet::Main))
-            .add_systems(Update, finish_execution.in_set(ActionSet::Finish).run_if(on_message::<ActionMessagePoolEmpty>));
+            .add_systems(Update, heal::heal_player.in_set(ActionSet::Main))
+            .add_systems(Update, shield::gain_shield.in_set(ActionSet::Main))
+            .add_systems(Update, draw::draw_cards.in_set(ActionSet::Main))
+            .add_systems(Update, moves::gain_moves.in_set(ActionSet::Main))
+            .add_systems(Update, recolor::recolor_tiles.in_set(ActionSet::Main))
+            .add_systems(Update, status::apply_status.in_set(ActionSet::Main));
     }
 }
 
 #[derive(Message, Default)]
 pub struct ExecuteActions;
 
-#[derive(Message)]
-pub struct FinishedExecution;
+/// Written together with the result of every action, for effects that follow the cast.
+#[derive(Message, Clone, Copy)]
+pub struct ActionResolved {
+    pub card: Entity,
+    pub kind: ActionKind,
+    pub target: Option<Entity>,
+}
 
-/// Combine the total value of all matching squares.
+/// Kind of an action, used to order several actions of a single card.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ActionKind {
+    Combine,
+    Shield,
+    Heal,
+    Moves,
+    Recolor,
+    Status,
+    Draw,
+}
+
+/// Order in which the actions of a card resolve.
+/// Cards without it resolve their actions in `ActionKind` declaration order.
 #[derive(Component)]
-pub struct ActionCombine;
+pub struct ActionOrder(pub Vec<ActionKind>);
+
+/// Card an action result originates from.
+#[derive(Clone, Debug)]
+pub struct ActionSource {
+    pub card_name: String,
+    /// Enemy hit by the action, only set for actions targeting enemies.
+    pub target: Option<Entity>,
+    pub color: GridTileColor,
+    pub crit: Option<CriticalHit>,
+    pub variance: Option<DamageVariance>,
+    pub matched: u64,
+    pub total: u64,
+    pub points: u64,
+}
+
+/// An action carried by a card, executed once per cast.
+pub trait CardAction: Component {
+    type Output: Message;
+
+    fn kind() -> ActionKind;
+
+    /// Actions targeting enemies resolve once for every target of the card.
+    fn targets_enemy() -> bool {
+        false
+    }
+
+    /// Message resolving the action for the points scored by the card.
+    fn resolve(&self, source: ActionSource) -> Option<Self::Output>;
+}
+
+/// Actions carried by a card, in `ActionKind` declaration order.
+type CardActionKinds = (
+    Has<ActionCombine>,
+    Has<ActionShield>,
+    Has<ActionHeal>,
+    Has<ActionGainMoves>,
+    Has<ActionRecolor>,
+    Has<ActionApplyStatus>,
+    Has<ActionDrawCards>,
+);
+
+fn action_kinds((combine, shield, heal, moves, recolor, status, draw): (bool, bool, bool, bool, bool, bool, bool)) -> impl Iterator<Item = ActionKind> {
+    [
+        (combine, ActionKind::Combine),
+        (shield, ActionKind::Shield),
+        (heal, ActionKind::Heal),
+        (moves, ActionKind::Moves),
+        (recolor, ActionKind::Recolor),
+        (status, ActionKind::Status),
+        (draw, ActionKind::Draw),
+    ]
+        .into_iter()
+        .filter_map(|(has, kind)| has.then_some(kind))
+}
+
+/// Position of the action within the card, kinds missing from the order go after the listed ones.
+fn action_step(order: Option<&ActionOrder>, kind: ActionKind) -> usize {
+    match order {
+        Some(order) => order.0
+            .iter()
+            .position(|k| *k == kind)
+            .unwrap_or(order.0.len() + kind as usize),
+        None => kind as usize,
+    }
+}
+
+/// Cards resolve left to right, actions of a single card one after another.
+/// The first action of the card waits for the card delay, the others for the step delay.
+fn action_task<M: Message>(card: &CardIndex, order: Option<&ActionOrder>, first_step: usize, kind: ActionKind, message: M) -> (f32, Task) {
+    let step = action_step(order, kind);
+    let delay = if step <= first_step {
+        CARD_DELAY_SECS
+    } else {
+        ACTION_STEP_SECS
+    };
+
+    (delay, Task::write_message(card.0 as u64 * 100 + step as u64, message))
+}
 
+/// Combine the total value of all matching squares.
 #[derive(Component)]
-pub struct ActionMessagePool;
+pub struct ActionCombine;
 
-#[derive(Message, Clone)]
-pub struct ActionMessagePoolEmpty;
+impl CardAction for ActionCombine {
+    type Output = DamageEnemy;
 
-fn setup_message_pool(
-    mut commands: Commands,
-) {
+    fn kind() -> ActionKind {
+        ActionKind::Combine
+    }
 
-    let mut pool = MessagePool::default();
-    pool.write_when_empty(ActionMessagePoolEmpty);
+    fn targets_enemy() -> bool {
+        true
+    }
 
-    // TODO: it's global, make it local per screen?
-    commands.spawn((
-        ActionMessagePool,
-        pool,
-    ));
+    fn resolve(&self, source: ActionSource) -> Option<DamageEnemy> {
+        Some(DamageEnemy {
+            target: source.target?,
+            amount: source.points,
+            damage_type: DamageType::from_color(source.color),
+            source,
+        })
+    }
 }
 
 #[derive(Message)]
-pub struct DamageEnemy(pub u64);
+pub struct DamageEnemy {
+    pub target: Entity,
+    pub amount: u64,
+    pub damage_type: DamageType,
+    pub source: ActionSource,
+}
 
-fn action_combine(
-    mut pool: Single<&mut MessagePool, With<ActionMessagePool>>,
+pub fn action_system<T: CardAction>(
+    mut tasks: Single<&mut Tasks, With<GameView>>,
     tiles_by_index: Single<&GridTileByIndex>,
     tiles: Query<&GridTileColor, With<GridTile>>,
-    query: Query<(&CardIndex, &CardRequirement), With<ActionCombine>>,
+    query: Query<(Entity, &CardIndex, &CardInfo, &CardRequirement, &CardScoring, &T, Option<&ActionOrder>, Option<&CriticalHit>, Option<&DamageVariance>, &CardTargets, CardActionKinds)>,
 ) {
-    println!("execute requested");
     query
         .into_iter()
-        .for_each(|(i, req)| {
-            let mut card_points = 0;
-            for (index, expected_color) in req.tiles.iter() {
-                if let Some(tile_entity) = tiles_by_index.get(index) {
-                    if let Some(color) = tiles.get(*tile_entity).ok() {
-                        if color.is_matching(expected_color) {
-                            card_points += 1;
-                        }
-                    }
-                }
+        .for_each(|(card, i, info, req, scoring, action, order, crit, variance, card_targets, kinds)| {
+            let first_step = action_kinds(kinds)
+                .map(|kind| action_step(order, kind))
+                .min()
+                .unwrap_or(0);
+
+            let matched = matched_tiles(req, &tiles_by_index, &tiles);
+            let total = req.tiles.len() as u64;
+            let source = ActionSource {
+                card_name: info.name.clone(),
+                target: None,
+                color: info.color,
+                crit: crit.copied(),
+                variance: variance.copied(),
+                matched,
+                total,
+                points: scoring.points(matched, total),
+            };
+
+            let targets: Vec<_> = if T::targets_enemy() {
+                card_targets.iter().copied().map(Some).collect()
+            } else {
+                vec![None]
+            };
+
+            // every target after the first one is hit at the same time
+            let mut hits = Vec::new();
+            for target in targets {
+                let Some(message) = action.resolve(ActionSource { target, ..source.clone() }) else {
+                    continue
+                };
+
+                let (delay, task) = action_task(i, order, first_step, T::kind(), message);
+                let resolved = Task::write_message(task.order(), ActionResolved {
+                    card,
+                    kind: T::kind(),
+                    target,
+                });
+                tasks.insert(if hits.is_empty() { delay } else { 0. }, resolved);
+                hits.push(task);
+            }
+
+            // damage lands together with the projectile launched on resolve
+            let travel = if T::kind() == ActionKind::Combine {
+                PROJECTILE_SECS
+            } else {
+                0.
+            };
+            for (n, task) in hits.into_iter().enumerate() {
+                tasks.insert(if n == 0 { travel } else { 0. }, task);
             }
-            
-            pool.write_after(DamageEnemy(card_points), i.0 as f32);
-            println!("action combine!");
         });
 }
 
-fn damage_enemy(
-    mut health: Single<&mut Health, With<Enemy>>,
-    mut reader: MessageReader<DamageEnemy>
-) {
-    for damage in reader.read() {
-        health.0 -= damage.0;
+/// Number of requirement tiles that currently match the board.
+pub fn matched_tiles(
+    req: &CardRequirement,
+    tiles_by_index: &GridTileByIndex,
+    tiles: &Query<&GridTileColor, With<GridTile>>,
+) -> u64 {
+    count_matched(req, |index| {
+        tiles_by_index
+            .get(index)
+            .and_then(|tile_entity| tiles.get(*tile_entity).ok())
+            .copied()
+    })
+}
+
+/// Number of requirement tiles matching the colors returned by `color_at`.
+pub fn count_matched(
+    req: &CardRequirement,
+    color_at: impl Fn(&Index) -> Option<GridTileColor>,
+) -> u64 {
+    let mut matched = 0;
+    for (index, expected_color) in req.tiles.iter() {
+        if let Some(color) = color_at(index) && color.is_matching(expected_color) {
+            matched += 1;
+        }
     }
+    matched
 }
 
-fn finish_execution(
-    mut pool: Single<&mut MessagePool, With<GlobalMessagePool>>,
+/// Card rules take precedence over the player's relics.
+/// Damage to an enemy that died earlier in the cast is lost.
+fn damage_enemy(
+    mut enemies: Query<(&mut Health, &mut Shield, Option<&Resistances>, Option<&Armor>, &StatusEffects), With<Enemy>>,
+    relics: Single<(Option<&CriticalHit>, Option<&DamageVariance>, &StatusEffects), (With<Player>, Without<Enemy>)>,
+    mut rng: Single<&mut WyRand, With<GlobalRng>>,
+    mut reader: MessageReader<DamageEnemy>,
+    mut dealt: MessageWriter<DamageDealt>,
+    mut log: MessageWriter<CombatLogEntry>,
 ) {
-    println!("empty message pool");
-    pool.write_after(FinishedExecution, 1.0);
-}
+    let (relic_crit, relic_variance, player_effects) = *relics;
+    for damage in reader.read() {
+        let Ok((mut health, mut shield, resistances, armor, effects)) = enemies.get_mut(damage.target) else {
+            continue
+        };
 
+        if health.0 == 0 {
+            continue
+        }
+
+        let crit = damage.source.crit.as_ref().or(relic_crit);
+        let variance = damage.source.variance.as_ref().or(relic_variance);
+        let roll = roll_damage(damage.amount, crit, variance, &mut **rng);
+
+        let target = HitTarget { resistances, armor, effects: Some(effects), shield: Some(&shield) };
+        let mut result = calculate_hit(roll.amount, damage.damage_type, Some(player_effects), target);
+        result.is_crit = roll.is_crit;
+        shield.0 -= result.blocked;
+        health.0 = health.0.saturating_sub(result.amount);
+
+        let mut entry = CombatLogEntry::new(&damage.source, "damage", result.amount)
+            .with_modifier(format!("{:?}", damage.damage_type))
+            .with_health(health.0);
+        if result.is_crit {
+            entry = entry.with_modifier("crit!".to_string());
+        }
+        if let Some(label) = result.effectiveness.label() {
+            entry = entry.with_modifier(label.to_string());
+        }
+        if result.absorbed > 0 {
+            entry = entry.with_modifier(format!("armor -{}", result.absorbed));
+        }
+        if result.blocked > 0 {
+            entry = entry.with_modifier(format!("shield -{}", result.blocked));
+        }
+        if player_effects.has(StatusEffect::Weak) {
+            entry = entry.with_modifier("weak".to_string());
+        }
+        if effects.has(StatusEffect::Vulnerable) {
+            entry = entry.with_modifier("vulnerable".to_string());
+        }
+        log.write(entry);
+
+        dealt.write(DamageDealt {
+            target: damage.target,
+            damage_type: damage.damage_type,
+            result,
+        });
+    }
+}
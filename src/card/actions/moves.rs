@@ -0,0 +1,45 @@
+use bevy::prelude::*;
+
+use crate::combat_log::CombatLogEntry;
+use crate::grid::{Grid, GridData};
+
+use super::{ActionKind, ActionSource, CardAction};
+
+/// Gain extra moves on the next board, one for every `per_point` points.
+#[derive(Component)]
+pub struct ActionGainMoves {
+    pub per_point: u64,
+}
+
+#[derive(Message)]
+pub struct GainMoves {
+    pub count: u64,
+    pub source: ActionSource,
+}
+
+impl CardAction for ActionGainMoves {
+    type Output = GainMoves;
+
+    fn kind() -> ActionKind {
+        ActionKind::Moves
+    }
+
+    fn resolve(&self, source: ActionSource) -> Option<GainMoves> {
+        let count = source.points / self.per_point.max(1);
+        (count > 0).then_some(GainMoves {
+            count,
+            source,
+        })
+    }
+}
+
+pub fn gain_moves(
+    mut data: Single<&mut GridData, With<Grid>>,
+    mut reader: MessageReader<GainMoves>,
+    mut log: MessageWriter<CombatLogEntry>,
+) {
+    for gain in reader.read() {
+        data.add_bonus_moves(gain.count as usize);
+        log.write(CombatLogEntry::new(&gain.source, "moves", gain.count));
+    }
+}
@@ -0,0 +1,47 @@
+use bevy::prelude::*;
+
+use crate::combat_log::CombatLogEntry;
+use crate::grid::{Grid, GridData, GridTileColor};
+
+use super::{ActionKind, ActionSource, CardAction};
+
+/// Recolor random tiles of the next board, `per_point` tiles for every point.
+#[derive(Component)]
+pub struct ActionRecolor {
+    pub color: GridTileColor,
+    pub per_point: u64,
+}
+
+#[derive(Message)]
+pub struct RecolorTiles {
+    pub color: GridTileColor,
+    pub count: u64,
+    pub source: ActionSource,
+}
+
+impl CardAction for ActionRecolor {
+    type Output = RecolorTiles;
+
+    fn kind() -> ActionKind {
+        ActionKind::Recolor
+    }
+
+    fn resolve(&self, source: ActionSource) -> Option<RecolorTiles> {
+        (source.points > 0).then(|| RecolorTiles {
+            color: self.color,
+            count: source.points * self.per_point,
+            source,
+        })
+    }
+}
+
+pub fn recolor_tiles(
+    mut data: Single<&mut GridData, With<Grid>>,
+    mut reader: MessageReader<RecolorTiles>,
+    mut log: MessageWriter<CombatLogEntry>,
+) {
+    for recolor in reader.read() {
+        data.add_recolor(recolor.color, recolor.count as usize);
+        log.write(CombatLogEntry::new(&recolor.source, "recolor", recolor.count));
+    }
+}
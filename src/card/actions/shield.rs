@@ -0,0 +1,41 @@
+use bevy::prelude::*;
+
+use crate::{combat_log::CombatLogEntry, healthbar::Shield, player::Player};
+
+use super::{ActionKind, ActionSource, CardAction};
+
+/// Shield the player for every point.
+#[derive(Component)]
+pub struct ActionShield(pub u64);
+
+#[derive(Message)]
+pub struct GainShield {
+    pub amount: u64,
+    pub source: ActionSource,
+}
+
+impl CardAction for ActionShield {
+    type Output = GainShield;
+
+    fn kind() -> ActionKind {
+        ActionKind::Shield
+    }
+
+    fn resolve(&self, source: ActionSource) -> Option<GainShield> {
+        (source.points > 0).then(|| GainShield {
+            amount: source.points * self.0,
+            source,
+        })
+    }
+}
+
+pub fn gain_shield(
+    mut shield: Single<&mut Shield, With<Player>>,
+    mut reader: MessageReader<GainShield>,
+    mut log: MessageWriter<CombatLogEntry>,
+) {
+    for gain in reader.read() {
+        shield.0 += gain.amount;
+        log.write(CombatLogEntry::new(&gain.source, "shield", gain.amount));
+    }
+}
@@ -0,0 +1,58 @@
+use bevy::prelude::*;
+
+use crate::{combat_log::CombatLogEntry, enemy::Enemy, status::{StatusEffect, StatusEffects}};
+
+use super::{ActionKind, ActionSource, CardAction};
+
+/// Apply `per_point` stacks of a status effect to the targeted enemies for every point.
+#[derive(Component)]
+pub struct ActionApplyStatus {
+    pub effect: StatusEffect,
+    pub per_point: u64,
+}
+
+#[derive(Message)]
+pub struct ApplyStatus {
+    pub target: Entity,
+    pub effect: StatusEffect,
+    pub stacks: u64,
+    pub source: ActionSource,
+}
+
+impl CardAction for ActionApplyStatus {
+    type Output = ApplyStatus;
+
+    fn kind() -> ActionKind {
+        ActionKind::Status
+    }
+
+    fn targets_enemy() -> bool {
+        true
+    }
+
+    fn resolve(&self, source: ActionSource) -> Option<ApplyStatus> {
+        let target = source.target?;
+        (source.points > 0).then(|| ApplyStatus {
+            target,
+            effect: self.effect,
+            stacks: source.points * self.per_point,
+            source,
+        })
+    }
+}
+
+pub fn apply_status(
+    mut enemies: Query<&mut StatusEffects, With<Enemy>>,
+    mut reader: MessageReader<ApplyStatus>,
+    mut log: MessageWriter<CombatLogEntry>,
+) {
+    for status in reader.read() {
+        let Ok(mut effects) = enemies.get_mut(status.target) else {
+            continue
+        };
+
+        effects.add(status.effect, status.stacks);
+        log.write(CombatLogEntry::new(&status.source, "status", status.stacks)
+            .with_modifier(format!("{:?}", status.effect)));
+    }
+}
@@ -0,0 +1,87 @@
+use bevy::prelude::*;
+use bevy_rand::{global::GlobalRng, prelude::WyRand};
+use rand::prelude::IndexedRandom;
+
+use crate::{card::CardIndex, enemy::{CurrentTarget, Enemy}, healthbar::Health};
+
+/// Enemies hit by the actions of a card.
+#[derive(Component, Clone, Copy, PartialEq, Debug, Default)]
+pub enum Targeting {
+    /// The enemy selected by the player.
+    #[default]
+    Single,
+    All,
+    Random,
+    LowestHealth,
+}
+
+impl Targeting {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Targeting::Single => "targets the selected enemy",
+            Targeting::All => "targets all enemies",
+            Targeting::Random => "targets a random enemy",
+            Targeting::LowestHealth => "targets the weakest enemy",
+        }
+    }
+
+    /// Enemies the card hits among the living ones sorted left to right, every one of them for `Random`.
+    pub fn candidates<T: Copy>(
+        &self,
+        living: &[T],
+        is_current: impl Fn(&T) -> bool,
+        health: impl Fn(&T) -> u64,
+    ) -> Vec<T> {
+        match self {
+            Targeting::Single => living
+                .iter()
+                .find(|enemy| is_current(enemy))
+                .or(living.first())
+                .copied()
+                .into_iter()
+                .collect(),
+            Targeting::All | Targeting::Random => living.to_vec(),
+            Targeting::LowestHealth => living
+                .iter()
+                .copied()
+                .reduce(|lowest, enemy| if health(&enemy) < health(&lowest) { enemy } else { lowest })
+                .into_iter()
+                .collect(),
+        }
+    }
+}
+
+/// Enemies chosen for the card when the cast starts.
+#[derive(Component, Default, Deref)]
+pub struct CardTargets(pub Vec<Entity>);
+
+/// Chosen once per cast, so all actions of a card hit the same enemies.
+pub fn choose_card_targets(
+    enemies: Query<(Entity, &Health, &Transform, Has<CurrentTarget>), With<Enemy>>,
+    mut rng: Single<&mut WyRand, With<GlobalRng>>,
+    cards: Query<(&CardIndex, &Targeting, &mut CardTargets)>,
+) {
+    // left to right, so ties and random picks don't depend on query order
+    let mut living: Vec<_> = enemies
+        .iter()
+        .filter(|(_, health, ..)| health.0 > 0)
+        .collect();
+    living.sort_by(|(_, _, a, _), (_, _, b, _)| a.translation.x.total_cmp(&b.translation.x));
+
+    let mut cards: Vec<_> = cards.into_iter().collect();
+    cards.sort_by_key(|(index, ..)| index.0);
+
+    for (_, targeting, mut targets) in cards {
+        let candidates = targeting.candidates(&living, |(.., is_current)| *is_current, |(_, health, ..)| health.0);
+        targets.0 = match targeting {
+            Targeting::Random => candidates
+                .choose(&mut rng)
+                .map(|(enemy, ..)| vec![*enemy])
+                .unwrap_or_default(),
+            _ => candidates
+                .into_iter()
+                .map(|(enemy, ..)| enemy)
+                .collect(),
+        };
+    }
+}
@@ -1,8 +1,10 @@
 use bevy::prelude::*;
 use maplit::hashmap;
 
-use crate::card::CardTrait;
-use crate::card::{actions::ActionCombine, CardRequirement};
+use crate::card::{CardScoring, CardTrait};
+use crate::card::{actions::{Targeting, ActionCombine, ActionApplyStatus}, CardRequirement};
+use crate::damage::CriticalHit;
+use crate::status::StatusEffect;
 use crate::grid::{Index, GridTileColor};
 
 #[derive(Component, Default)]
@@ -13,12 +15,26 @@ impl CardTrait for CardCrocodile {
         "green_card.png".into()
     }
 
+    fn color() -> GridTileColor {
+        GridTileColor::Green
+    }
+
     fn sprite_name() -> String {
         "crocodile.png".into()
     }
 
     fn actions() -> impl Bundle {
-        ActionCombine
+        (
+            ActionCombine,
+            ActionApplyStatus {
+                effect: StatusEffect::Poison,
+                per_point: 1,
+            },
+            CriticalHit {
+                chance: 0.25,
+                multiplier: 2.0,
+            },
+        )
     }
 
     fn fixed_requirements() -> CardRequirement {
@@ -30,8 +46,20 @@ impl CardTrait for CardCrocodile {
         }
     }
 
+    fn targeting() -> Targeting {
+        Targeting::Random
+    }
+
+    fn scoring() -> CardScoring {
+        CardScoring::BonusOnComplete(2)
+    }
+
     fn card_name() -> String {
         "Crocodile".into()
     }
+
+    fn description() -> String {
+        "Deal 1 damage and apply 1 poison for every point.".into()
+    }
 }
 
@@ -3,8 +3,8 @@ use bevy_rand::prelude::WyRand;
 use maplit::hashmap;
 use rand::Rng;
 
-use crate::card::CardTrait;
-use crate::card::{actions::ActionCombine, CardRequirement};
+use crate::card::{CardScoring, CardTrait};
+use crate::card::{actions::{Targeting, ActionCombine, ActionShield}, CardRequirement};
 use crate::grid::{Index, GridTileColor, GridConfig};
 
 #[derive(Component, Default)]
@@ -15,30 +15,51 @@ impl CardTrait for CardDiamond {
         "blue_card.png".into()
     }
 
+    fn color() -> GridTileColor {
+        GridTileColor::Blue
+    }
+
     fn sprite_name() -> String {
         "diamond.png".into()
     }
 
+    fn targeting() -> Targeting {
+        Targeting::LowestHealth
+    }
+
     fn actions() -> impl Bundle {
-        ActionCombine
+        (
+            ActionCombine,
+            ActionShield(2),
+        )
     }
 
     fn requirements(
         rng: &mut WyRand,
         config: &GridConfig,
     ) -> CardRequirement {
-        let x = rng.random_range(0usize..config.dimensions.0);
+        // two neighbouring tiles, so all or nothing differs from one point per tile
+        let x = rng.random_range(0usize..config.dimensions.0.saturating_sub(1).max(1));
         let y = rng.random_range(0usize..config.dimensions.1);
             
         CardRequirement {
             tiles: hashmap! {
                 Index::new(x, y) => GridTileColor::Blue,
+                Index::new(x + 1, y) => GridTileColor::Blue,
             }.into_iter().collect()
         }
     }
 
+    fn scoring() -> CardScoring {
+        CardScoring::AllOrNothing
+    }
+
     fn card_name() -> String {
         "Diamond".into()
     }
+
+    fn description() -> String {
+        "Deal 1 damage and gain 2 shield for every point, only once both tiles match.".into()
+    }
 }
 
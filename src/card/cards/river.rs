@@ -1,8 +1,9 @@
 use bevy::prelude::*;
 use maplit::hashmap;
 
-use crate::card::CardTrait;
-use crate::card::{actions::ActionCombine, CardRequirement};
+use crate::card::{CardScoring, CardTrait};
+use crate::card::{actions::{Targeting, ActionCombine, ActionGainMoves}, CardRequirement};
+use crate::damage::DamageVariance;
 use crate::grid::{Index, GridTileColor};
 
 #[derive(Component, Default)]
@@ -13,12 +14,26 @@ impl CardTrait for CardRiver {
         "blue_card.png".into()
     }
 
+    fn color() -> GridTileColor {
+        GridTileColor::Blue
+    }
+
     fn sprite_name() -> String {
         "river.png".into()
     }
 
     fn actions() -> impl Bundle {
-        ActionCombine
+        (
+            ActionCombine,
+            ActionGainMoves {
+                // one move for completing the card
+                per_point: Self::scoring().max_points(Self::fixed_requirements().tiles.len() as u64),
+            },
+            DamageVariance {
+                min: 0.75,
+                max: 1.25,
+            },
+        )
     }
 
     fn fixed_requirements() -> CardRequirement {
@@ -33,8 +48,20 @@ impl CardTrait for CardRiver {
         }
     }
 
+    fn targeting() -> Targeting {
+        Targeting::All
+    }
+
+    fn scoring() -> CardScoring {
+        CardScoring::Thresholds(vec![(3, 4), (5, 12)])
+    }
+
     fn card_name() -> String {
         "River".into()
     }
+
+    fn description() -> String {
+        "Deal damage by tier. Gain a move next turn when complete.".into()
+    }
 }
 
@@ -0,0 +1,219 @@
+use bevy::prelude::*;
+
+use crate::core::prelude::*;
+use crate::grid::{GridConfig, GridTile, GridTileByIndex, GridTileColor, Index};
+use crate::layout::{ContentView, GameView};
+
+use super::{actions::Targeting, preview::DamagePreview, tasks::Tasks, Card, CardInfo, CardRequirement, CardScoring};
+
+/// Size of a single tile on the requirement mini-map.
+const MINIMAP_TILE_SIZE: f32 = 20.;
+
+#[derive(Message)]
+pub struct CardInspectRequest(pub Entity);
+
+/// Root of the inspect overlay, despawned on the next click.
+#[derive(Component)]
+pub struct CardInspectView;
+
+pub struct CardInspectPlugin;
+
+impl Plugin for CardInspectPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_message::<CardInspectRequest>()
+            .add_systems(Update, close_inspect
+                .run_if(action_just_pressed(InputAction::Pick).or(action_just_pressed(InputAction::Cancel)))
+            )
+            .add_systems(Update, inspect_on_cancel
+                .run_if(action_just_pressed(InputAction::Cancel))
+                .after(close_inspect)
+            )
+            .add_systems(Update, inspect_on_gesture
+                .run_if(on_message::<LongPress>.or(on_message::<DoubleTap>))
+            )
+            .add_systems(PostUpdate, pause_while_inspecting)
+            .add_systems(Update, display_inspect
+                .run_if(on_message::<CardInspectRequest>)
+                .after(inspect_on_cancel)
+                .after(inspect_on_gesture)
+            );
+    }
+}
+
+fn inspect_on_cancel(
+    cards: Query<(Entity, &TouchState), With<Card>>,
+    mut writer: MessageWriter<CardInspectRequest>,
+) {
+    if let Some((entity, _)) = cards.iter().find(|(_, state)| state.is_touching()) {
+        writer.write(CardInspectRequest(entity));
+    }
+}
+
+/// Long press or double tap on a card inspects it.
+fn inspect_on_gesture(
+    cards: Query<(), With<Card>>,
+    mut long_presses: MessageReader<LongPress>,
+    mut double_taps: MessageReader<DoubleTap>,
+    mut writer: MessageWriter<CardInspectRequest>,
+) {
+    let entities = long_presses.read().map(|press| press.entity)
+        .chain(double_taps.read().map(|tap| tap.entity));
+    for entity in entities {
+        if cards.contains(entity) {
+            writer.write(CardInspectRequest(entity));
+        }
+    }
+}
+
+fn close_inspect(
+    mut commands: Commands,
+    views: Query<Entity, With<CardInspectView>>,
+) {
+    views
+        .into_iter()
+        .for_each(|view| {
+            commands.entity(view).despawn();
+        });
+}
+
+/// The cast waits while a card is inspected.
+fn pause_while_inspecting(
+    added: Query<(), Added<CardInspectView>>,
+    mut removed: RemovedComponents<CardInspectView>,
+    views: Query<(), With<CardInspectView>>,
+    mut screens: Query<&mut Tasks, With<GameView>>,
+) {
+    if added.is_empty() && removed.read().next().is_none() {
+        return
+    }
+
+    for mut tasks in &mut screens {
+        if views.is_empty() {
+            tasks.resume();
+        } else {
+            tasks.pause();
+        }
+    }
+}
+
+fn display_inspect(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    config: Res<GridConfig>,
+    content_view: Single<Entity, With<ContentView>>,
+    existing: Query<Entity, With<CardInspectView>>,
+    cards: Query<(&CardInfo, &CardRequirement, &CardScoring, &Targeting), With<Card>>,
+    tiles_by_index: Single<&GridTileByIndex>,
+    tiles: Query<&GridTileColor, With<GridTile>>,
+    preview: DamagePreview,
+    mut reader: MessageReader<CardInspectRequest>,
+) {
+    let Some(request) = reader.read().last() else {
+        return
+    };
+
+    let Ok((info, req, scoring, targeting)) = cards.get(request.0) else {
+        return
+    };
+
+    existing
+        .into_iter()
+        .for_each(|entity| {
+            commands.entity(entity).despawn();
+        });
+
+    let card_area = Vec2::new(128., 192.);
+    let mut bg_sprite = Sprite::from_image(asset_server.load(info.background_sprite_name.clone()));
+    let mut sprite = Sprite::from_image(asset_server.load("cards/images/".to_string() + &info.sprite_name));
+    bg_sprite.custom_size = Some(card_area);
+    sprite.custom_size = Some(card_area);
+
+    let predicted = preview.card_damage(info, req, scoring, targeting);
+
+    commands
+        .entity(*content_view)
+        .with_children(|view| {
+            view.spawn((
+                CardInspectView,
+                Pickable::default(),
+                Name::new("Card Inspect"),
+                Sprite::from_color(Color::linear_rgba(0., 0., 0., 0.85), Vec2::new(800., 600.)),
+                Transform::from_xyz(0., 0., 10.),
+            )).with_children(|overlay| {
+                overlay.spawn((
+                    bg_sprite,
+                    Transform::from_xyz(-160., 0., 1.),
+                ));
+                overlay.spawn((
+                    sprite,
+                    Transform::from_xyz(-160., 0., 2.),
+                ));
+                overlay.spawn((
+                    Text2d::new(info.name.clone()),
+                    Transform::from_xyz(-160., 120., 1.),
+                ));
+
+                overlay.spawn((
+                    Name::new("Requirement Mini-map"),
+                    Transform::from_xyz(120., 64., 1.),
+                    Visibility::Inherited,
+                )).with_children(|minimap| {
+                    let scale = MINIMAP_TILE_SIZE / config.tile_size.x;
+                    for x in 0..config.dimensions.0 {
+                        for y in 0..config.dimensions.1 {
+                            let index = Index::new(x, y);
+                            let position = config.xy_position(&index) * scale;
+                            let color = minimap_tile_color(req, &index, &tiles_by_index, &tiles);
+
+                            minimap.spawn((
+                                Sprite::from_color(color, Vec2::splat(MINIMAP_TILE_SIZE - 2.)),
+                                Transform::from_xyz(position.x, position.y, 0.),
+                            ));
+                        }
+                    }
+                });
+
+                overlay.spawn((
+                    Text2d::new(info.description.clone()),
+                    Transform::from_xyz(120., -32., 1.),
+                ));
+                overlay.spawn((
+                    Text2d::new(scoring.label(req.tiles.len() as u64)),
+                    Transform::from_xyz(-160., -130., 1.),
+                ));
+                overlay.spawn((
+                    Text2d::new(format!("predicted damage: {}", predicted)),
+                    Transform::from_xyz(120., -64., 1.),
+                ));
+                overlay.spawn((
+                    Text2d::new(targeting.label()),
+                    Transform::from_xyz(120., -96., 1.),
+                ));
+            });
+        });
+}
+
+/// Required cells are drawn in their color, dimmed until the board matches them.
+fn minimap_tile_color(
+    req: &CardRequirement,
+    index: &Index,
+    tiles_by_index: &GridTileByIndex,
+    tiles: &Query<&GridTileColor, With<GridTile>>,
+) -> Color {
+    let Some(expected_color) = req.tiles.get(index) else {
+        return Color::linear_rgb(0.15, 0.15, 0.15)
+    };
+
+    let is_matching = tiles_by_index
+        .get(index)
+        .and_then(|entity| tiles.get(*entity).ok())
+        .map(|color| color.is_matching(expected_color))
+        .unwrap_or(false);
+
+    if is_matching {
+        expected_color.color()
+    } else {
+        expected_color.color().with_alpha(0.35)
+    }
+}
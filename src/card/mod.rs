@@ -1,16 +1,27 @@
 pub mod actions;
 mod cards;
-mod tasks;
+mod inspect;
+mod preview;
+mod resolving;
+mod scoring;
+pub mod tasks;
 
 
-use rand::prelude::IndexedRandom;
+use rand::{prelude::IndexedRandom, seq::SliceRandom};
 
 use bevy::{platform::collections::HashMap, prelude::*};
 use bevy_rand::{global::GlobalRng, prelude::WyRand};
 
 use crate::{core::prelude::*, grid::GridConfig};
+use actions::{CardTargets, Targeting};
 use cards::{CardCrocodile, CardDiamond, CardRiver};
-use crate::{grid::{GridTileColor, Index}, grid_highlight::{GridHighlightRequest, GridHighlightsState, GridTileHighlightSide}, scale_on_touch::ScaleOnTouch, tooltip_on_touch::TooltipOnTouch};
+use inspect::CardInspectPlugin;
+use preview::{CardPreviewLabel, CardPreviewPlugin};
+use resolving::CardResolvingPlugin;
+use tasks::TaskPlugin;
+pub use preview::CastPreviewLabel;
+pub use scoring::CardScoring;
+use crate::{grid::{GridTileByIndex, GridTileColor, GridTileEffect, Index}, grid_highlight::{GridHighlightRequest, GridHighlightsState, GridTileHighlightSide}, notification::{DisplayToast, NotificationKind}, scale_on_touch::ScaleOnTouch, tooltip_on_touch::TooltipOnTouch};
 
 #[derive(Message, Default)]
 pub struct CardRedrawRequest;
@@ -20,6 +31,16 @@ pub struct CardPlugin;
 #[derive(Component)]
 pub struct Card;
 
+/// Static description of a spawned card, used by views that need to redraw it.
+#[derive(Component, Clone)]
+pub struct CardInfo {
+    pub name: String,
+    pub description: String,
+    pub background_sprite_name: String,
+    pub sprite_name: String,
+    pub color: GridTileColor,
+}
+
 #[derive(Component)]
 pub struct CardRequirement {
     pub tiles: HashMap<Index, GridTileColor>,
@@ -28,9 +49,14 @@ pub struct CardRequirement {
 impl Plugin for CardPlugin {
     fn build(&self, app: &mut App) {
         app
+            .add_plugins(CardInspectPlugin)
+            .add_plugins(CardPreviewPlugin)
+            .add_plugins(CardResolvingPlugin)
+            .add_plugins(TaskPlugin)
             .add_message::<CardRedrawRequest>()
             .add_systems(Startup, setup_all_cards_collection)
             .add_systems(Update, card_highlight2)
+            .add_systems(Update, scramble_requirements.before(card_highlight2))
             .add_systems(Update, setup_card)
             .add_systems(Update, card_random)
             .add_systems(Update, setup_cards_view)
@@ -43,16 +69,19 @@ impl Plugin for CardPlugin {
 
 fn card_highlight2(
     mut state: Single<&mut GridHighlightsState>,
-    cards: Query<(&CardIndex, &CardRequirement), Added<CardRequirement>>,
+    cards: Query<(&CardIndex, &CardRequirement, &CardScoring), Added<CardRequirement>>,
     mut request: MessageWriter<GridHighlightRequest>
 ) {
-    for (index, req) in cards {
+    for (index, req, scoring) in cards {
         let side = match **index {
             0 => GridTileHighlightSide::Left,
             1 => GridTileHighlightSide::Bottom,
-            _ => GridTileHighlightSide::Right,
+            2 => GridTileHighlightSide::Right,
+            // extra drawn cards have no side left to highlight on
+            _ => continue,
         };
 
+        state.scoring_by_side.insert(side, scoring.clone());
         state.highlights_by_side.insert(side, req.tiles.clone());
     }
 
@@ -61,6 +90,52 @@ fn card_highlight2(
     }
 }
 
+/// Moves the requirement cells of random cards of the new hand, keeping their colors.
+fn scramble_requirements(
+    config: Res<GridConfig>,
+    mut scrambled: Single<&mut ScrambledCards, With<CardsView>>,
+    mut rng: Single<&mut WyRand, With<GlobalRng>>,
+    tiles_by_index: Single<&GridTileByIndex>,
+    mut tile_effects: Query<&mut GridTileEffect>,
+    mut cards: Query<(&CardIndex, &CardInfo, &mut CardRequirement, &mut TooltipOnTouch), Added<CardRequirement>>,
+    mut toasts: MessageWriter<DisplayToast>,
+) {
+    if scrambled.0 == 0 || cards.is_empty() {
+        return
+    }
+
+    // only the highlighted cards, so the player can see where the cells went
+    let mut targets: Vec<_> = cards
+        .iter_mut()
+        .filter(|(index, ..)| index.0 < HAND_SIZE)
+        .collect();
+    targets.shuffle(&mut rng);
+
+    for (_, info, mut req, mut tooltip) in targets.into_iter().take(scrambled.0) {
+        let mut indices: Vec<_> = (0..config.dimensions.0)
+            .flat_map(|x| (0..config.dimensions.1).map(move |y| Index::new(x, y)))
+            .collect();
+        indices.shuffle(&mut rng);
+
+        let colors: Vec<_> = req.tiles.values().copied().collect();
+        req.tiles = indices.into_iter().zip(colors).collect();
+
+        for index in req.tiles.keys() {
+            if let Some(mut effect) = tiles_by_index.get(index).and_then(|tile| tile_effects.get_mut(*tile).ok()) {
+                effect.set_if_neq(GridTileEffect::Scrambled);
+            }
+        }
+
+        tooltip.0 = format!("{} (requirements scrambled by an enemy)", info.name);
+    }
+
+    toasts.write(DisplayToast {
+        text: "Cards scrambled".into(),
+        kind: NotificationKind::Warning,
+    });
+    scrambled.0 = 0;
+}
+
 //fn card_highlight(
     //cards: Query<(&TouchState, &CardRequirement, &CardIndex), (With<Card>, Changed<TouchState>)>,
     //mut request: MessageWriter<GridHighlightRequest>
@@ -107,7 +182,8 @@ fn setup_card(
                     TouchArea {
                         area: card_area,
                     },
-                    //PressArea,
+                    PressArea,
+                    GestureArea,
                     ScaleOnTouch(1.2),
                 ));
         });
@@ -172,6 +248,8 @@ pub fn card_random(
 
 pub trait CardTrait: Component {
     fn background_sprite_name() -> String;
+    /// Color of the card, determines the type of damage it deals.
+    fn color() -> GridTileColor;
     fn sprite_name() -> String;
     fn actions() -> impl Bundle;
 
@@ -188,7 +266,18 @@ pub trait CardTrait: Component {
         Self::fixed_requirements()
     }
 
+    fn scoring() -> CardScoring {
+        CardScoring::Linear
+    }
+
+    fn targeting() -> Targeting {
+        Targeting::Single
+    }
+
     fn card_name() -> String;
+
+    /// Human readable description of what the card's actions do.
+    fn description() -> String;
 }
 
 pub fn card_system<T: CardTrait>(
@@ -207,11 +296,25 @@ pub fn card_system<T: CardTrait>(
             bg_sprite.custom_size = Some(card_area);
             sprite.custom_size = Some(card_area);
 
+            let requirements = T::requirements(&mut rng, &config);
+            let scoring = T::scoring();
+            let scoring_label = scoring.label(requirements.tiles.len() as u64);
+
             commands.entity(e)
                 .try_insert((
                     T::actions(),
-                    T::requirements(&mut rng, &config),
-                    TooltipOnTouch(T::card_name())
+                    requirements,
+                    scoring,
+                    T::targeting(),
+                    CardTargets::default(),
+                    TooltipOnTouch(T::card_name()),
+                    CardInfo {
+                        name: T::card_name(),
+                        description: T::description(),
+                        background_sprite_name: T::background_sprite_name(),
+                        sprite_name: T::sprite_name(),
+                        color: T::color(),
+                    },
                 ))
                 .with_children(|e| {
                     e.spawn((
@@ -222,6 +325,23 @@ pub fn card_system<T: CardTrait>(
                         sprite,
                         Transform::from_xyz(0., 0., 1.)
                     ));
+                    e.spawn((
+                        Text2d::new(scoring_label),
+                        TextFont {
+                            font_size: 10.,
+                            ..default()
+                        },
+                        Transform::from_xyz(0., -36., 2.)
+                    ));
+                    e.spawn((
+                        CardPreviewLabel,
+                        Text2d::new(""),
+                        TextFont {
+                            font_size: 12.,
+                            ..default()
+                        },
+                        Transform::from_xyz(0., 58., 2.)
+                    ));
                 });
         });
 }
@@ -232,57 +352,70 @@ pub struct CardIndex(usize);
 #[derive(Component)]
 pub struct CardsView;
 
+/// Number of cards in a regular hand.
+const HAND_SIZE: usize = 3;
+
+/// Upper limit of cards in a single hand.
+pub const MAX_HAND_SIZE: usize = 5;
+
+/// Cards drawn on top of `HAND_SIZE` with the next redraw.
+#[derive(Component, Default, Deref, DerefMut)]
+pub struct ExtraCards(pub usize);
+
+/// Cards of the next hand whose requirement cells are moved around the board.
+#[derive(Component, Default, Deref, DerefMut)]
+pub struct ScrambledCards(pub usize);
+
 fn setup_cards_view(
-    commands: Commands,
+    mut commands: Commands,
     cards_view: Single<Entity, Added<CardsView>>,
 ) {
-    redraw_cards_impl(commands, *cards_view);
+    commands.entity(*cards_view).try_insert((ExtraCards(0), ScrambledCards(0)));
+    redraw_cards_impl(commands, *cards_view, HAND_SIZE);
 }
 
 /// Should be called on_event only
 pub fn redraw_cards(
     mut commands: Commands,
-    cards_view: Single<(Entity, Option<&Children>), With<CardsView>>, 
+    mut cards_view: Single<(Entity, Option<&Children>, Option<&mut ExtraCards>), With<CardsView>>,
 ) {
-    let (cards_view, children) = *cards_view;
+    let (cards_view, children, ref mut extra) = *cards_view;
     if let Some(children) = children {
         for child in children.iter() {
             commands.entity(child).despawn();
         }
     }
 
-    redraw_cards_impl(commands, cards_view);
+    let hand_size = match extra {
+        Some(extra) => {
+            let hand_size = (HAND_SIZE + extra.0).min(MAX_HAND_SIZE);
+            extra.0 = 0;
+            hand_size
+        },
+        None => HAND_SIZE,
+    };
+
+    redraw_cards_impl(commands, cards_view, hand_size);
 }
 
 fn redraw_cards_impl(
     mut commands: Commands,
     cards_view: Entity,
+    hand_size: usize,
 ) {
     println!("redraw cards");
     commands 
         .entity(cards_view)
         .with_children(|view| {
-            view.spawn((
-                Card,
-                CardIndex(0),
-                CardRandom,
-                Transform::from_xyz(-96., 0., 0.),
-                Visibility::Inherited,
-            ));
-            view.spawn((
-                Card,
-                CardIndex(1),
-                CardRandom,
-                Transform::from_xyz(0., 0., 0.),
-                Visibility::Inherited,
-            ));
-            view.spawn((
-                Card,
-                CardIndex(2),
-                CardRandom,
-                Transform::from_xyz(96., 0., 0.),
-                Visibility::Inherited,
-            ));
+            for i in 0..hand_size {
+                let x = (i as f32 - (hand_size - 1) as f32 / 2.) * 96.;
+                view.spawn((
+                    Card,
+                    CardIndex(i),
+                    CardRandom,
+                    Transform::from_xyz(x, 0., 0.),
+                    Visibility::Inherited,
+                ));
+            }
         });
 }
-
@@ -0,0 +1,192 @@
+use bevy::{ecs::system::SystemParam, platform::collections::HashMap, prelude::*};
+
+use crate::damage::{calculate_hit, Armor, DamageType, HitTarget, Resistances};
+use crate::enemy::{CurrentTarget, Enemy};
+use crate::grid::{GridTile, GridTileColor, Index};
+use crate::healthbar::{Health, Shield};
+use crate::player::Player;
+use crate::status::StatusEffects;
+
+use super::{actions::{count_matched, ActionCombine, Targeting}, CardInfo, CardRequirement, CardScoring};
+
+/// Damage the card would deal if cast now, displayed above the card.
+#[derive(Component)]
+pub struct CardPreviewLabel;
+
+/// Total damage of the hand if cast now, displayed next to the cast button.
+#[derive(Component)]
+pub struct CastPreviewLabel;
+
+pub struct CardPreviewPlugin;
+
+impl Plugin for CardPreviewPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(PostUpdate, update_preview);
+    }
+}
+
+/// Damage a card would deal against the board, without critical hits or variance.
+pub fn predict_damage(
+    req: &CardRequirement,
+    scoring: &CardScoring,
+    color: GridTileColor,
+    board: &HashMap<Index, GridTileColor>,
+    attacker: Option<&StatusEffects>,
+    target: HitTarget,
+) -> u64 {
+    let matched = count_matched(req, |index| board.get(index).copied());
+    let points = scoring.points(matched, req.tiles.len() as u64);
+    calculate_hit(points, DamageType::from_color(color), attacker, target).amount
+}
+
+type EnemyDefenses = (
+    &'static Health,
+    &'static Transform,
+    Has<CurrentTarget>,
+    Option<&'static Resistances>,
+    Option<&'static Armor>,
+    Option<&'static StatusEffects>,
+    Option<&'static Shield>,
+);
+
+/// Everything the damage of a card depends on, shared by the card labels and the inspect view.
+#[derive(SystemParam)]
+pub struct DamagePreview<'w, 's> {
+    tiles: Query<'w, 's, (&'static Index, &'static GridTileColor), With<GridTile>>,
+    enemies: Query<'w, 's, EnemyDefenses, With<Enemy>>,
+    player: Option<Single<'w, 's, &'static StatusEffects, With<Player>>>,
+}
+
+impl DamagePreview<'_, '_> {
+    /// Damage against the enemies the card targets, summed for `All` and a range for `Random`.
+    pub fn card_damage(
+        &self,
+        info: &CardInfo,
+        req: &CardRequirement,
+        scoring: &CardScoring,
+        targeting: &Targeting,
+    ) -> PredictedDamage {
+        let board: HashMap<Index, GridTileColor> = self.tiles
+            .iter()
+            .map(|(index, color)| (*index, *color))
+            .collect();
+
+        // left to right like the targets chosen for the cast
+        let mut living: Vec<_> = self.enemies
+            .iter()
+            .filter(|(health, ..)| health.0 > 0)
+            .collect();
+        living.sort_by(|(_, a, ..), (_, b, ..)| a.translation.x.total_cmp(&b.translation.x));
+
+        let attacker = self.player.as_deref().copied();
+        let damages: Vec<_> = targeting
+            .candidates(&living, |(_, _, is_current, ..)| *is_current, |(health, ..)| health.0)
+            .into_iter()
+            .map(|(_, _, _, resistances, armor, effects, shield)| {
+                let target = HitTarget { resistances, armor, effects, shield };
+                predict_damage(req, scoring, info.color, &board, attacker, target)
+            })
+            .collect();
+
+        let (min, max) = match targeting {
+            Targeting::All => (damages.iter().sum(), damages.iter().sum()),
+            _ => (damages.iter().copied().min().unwrap_or(0), damages.iter().copied().max().unwrap_or(0)),
+        };
+
+        PredictedDamage {
+            min,
+            max,
+            random: *targeting == Targeting::Random,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct PredictedDamage {
+    pub min: u64,
+    pub max: u64,
+    /// Hits one enemy picked when the cast starts.
+    pub random: bool,
+}
+
+impl std::fmt::Display for PredictedDamage {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.min == self.max {
+            write!(f, "{} dmg", self.min)?;
+        } else {
+            write!(f, "{}-{} dmg", self.min, self.max)?;
+        }
+        if self.random {
+            write!(f, " random")?;
+        }
+        Ok(())
+    }
+}
+
+/// Recomputed whenever tiles are swapped or recolored and whenever cards are drawn.
+fn update_preview(
+    preview: DamagePreview,
+    changed_tiles: Query<(), (With<GridTile>, Or<(Changed<Index>, Changed<GridTileColor>)>)>,
+    added_cards: Query<(), Added<CardRequirement>>,
+    added_labels: Query<(), Added<CastPreviewLabel>>,
+    changed_enemies: Query<(), (With<Enemy>, Or<(Added<CurrentTarget>, Changed<Health>, Changed<Shield>)>)>,
+    changed_effects: Query<(), Changed<StatusEffects>>,
+    cards: Query<(&CardInfo, &CardRequirement, &CardScoring, &Targeting, &Children), With<ActionCombine>>,
+    mut card_labels: Query<&mut Text2d, (With<CardPreviewLabel>, Without<CastPreviewLabel>)>,
+    mut cast_labels: Query<&mut Text2d, (With<CastPreviewLabel>, Without<CardPreviewLabel>)>,
+) {
+    if changed_tiles.is_empty() && added_cards.is_empty() && added_labels.is_empty() && changed_enemies.is_empty() && changed_effects.is_empty() {
+        return
+    }
+
+    let mut total = PredictedDamage::default();
+    for (info, req, scoring, targeting, children) in &cards {
+        let damage = preview.card_damage(info, req, scoring, targeting);
+        total.min += damage.min;
+        total.max += damage.max;
+
+        let mut labels = card_labels.iter_many_mut(children);
+        while let Some(mut text) = labels.fetch_next() {
+            *text = Text2d::new(damage.to_string());
+        }
+    }
+
+    for mut text in &mut cast_labels {
+        *text = Text2d::new(format!("total {}", total));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::status::StatusEffect;
+
+    #[test]
+    fn test_predict_damage() {
+        let req = CardRequirement {
+            tiles: HashMap::from_iter([
+                (Index::new(0, 0), GridTileColor::Green),
+                (Index::new(1, 0), GridTileColor::Green),
+            ]),
+        };
+        let mut board = HashMap::new();
+        board.insert(Index::new(0, 0), GridTileColor::Green);
+        board.insert(Index::new(1, 0), GridTileColor::Red);
+
+        assert_eq!(1, predict_damage(&req, &CardScoring::Linear, GridTileColor::Green, &board, None, HitTarget::default()));
+
+        board.insert(Index::new(1, 0), GridTileColor::Multicolor);
+        assert_eq!(2, predict_damage(&req, &CardScoring::Linear, GridTileColor::Green, &board, None, HitTarget::default()));
+        let armored = HitTarget { armor: Some(&Armor(1)), ..default() };
+        assert_eq!(1, predict_damage(&req, &CardScoring::Linear, GridTileColor::Green, &board, None, armored));
+
+        let mut vulnerable = StatusEffects::default();
+        vulnerable.add(StatusEffect::Vulnerable, 1);
+        let target = HitTarget { effects: Some(&vulnerable), ..default() };
+        assert_eq!(3, predict_damage(&req, &CardScoring::Linear, GridTileColor::Green, &board, None, target));
+
+        let shielded = HitTarget { shield: Some(&Shield(2)), ..default() };
+        assert_eq!(0, predict_damage(&req, &CardScoring::Linear, GridTileColor::Green, &board, None, shielded));
+    }
+}
@@ -0,0 +1,181 @@
+use std::f32::consts::PI;
+
+use bevy::{platform::collections::HashSet, prelude::*};
+
+use crate::enemy::Enemy;
+use crate::game::GameSpeed;
+use crate::grid::{GridConfig, GridTileByIndex};
+use crate::layout::GameView;
+
+use super::{actions::{ActionKind, ActionResolved}, CardInfo, CardRequirement};
+
+/// How long a card stays lifted after one of its actions resolves.
+const LIFT_SECS: f32 = 0.5;
+
+const LIFT_HEIGHT: f32 = 16.;
+
+/// How long the requirement cells of a resolving card flash.
+const FLASH_SECS: f32 = 0.4;
+
+/// Travel time of the projectile from the card to the enemy.
+pub(super) const PROJECTILE_SECS: f32 = 0.25;
+
+#[derive(Component)]
+struct CardLift(Timer);
+
+#[derive(Component)]
+struct CardGlow;
+
+#[derive(Component)]
+struct TileFlash(Timer);
+
+#[derive(Component)]
+struct Projectile {
+    from: Vec3,
+    to: Vec3,
+    timer: Timer,
+}
+
+pub struct CardResolvingPlugin;
+
+impl Plugin for CardResolvingPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Update, start_resolve_effects.run_if(on_message::<ActionResolved>))
+            .add_systems(Update, lift_cards.after(start_resolve_effects))
+            .add_systems(Update, flash_tiles)
+            .add_systems(Update, move_projectiles);
+    }
+}
+
+fn start_resolve_effects(
+    mut commands: Commands,
+    config: Res<GridConfig>,
+    game_view: Single<(Entity, &GlobalTransform), With<GameView>>,
+    enemies: Query<&GlobalTransform, With<Enemy>>,
+    tiles_by_index: Single<&GridTileByIndex>,
+    cards: Query<(&CardInfo, &CardRequirement, &GlobalTransform, Option<&Children>)>,
+    glows: Query<(), With<CardGlow>>,
+    mut reader: MessageReader<ActionResolved>,
+) {
+    let (game_view, view_transform) = *game_view;
+    let mut glowing = HashSet::new();
+
+    for resolved in reader.read() {
+        let Ok((info, req, card_transform, children)) = cards.get(resolved.card) else {
+            continue
+        };
+
+        let color = info.color.color();
+
+        let has_glow = children
+            .map(|children| children.iter().any(|child| glows.contains(child)))
+            .unwrap_or(false);
+
+        commands
+            .entity(resolved.card)
+            .try_insert(CardLift(Timer::from_seconds(LIFT_SECS, TimerMode::Once)));
+
+        if !has_glow && glowing.insert(resolved.card) {
+            commands
+                .entity(resolved.card)
+                .with_child((
+                    CardGlow,
+                    Sprite::from_color(color.with_alpha(0.), Vec2::new(72., 104.)),
+                    Transform::from_xyz(0., 0., -0.5),
+                ));
+        }
+
+        for index in req.tiles.keys() {
+            if let Some(tile) = tiles_by_index.get(index) {
+                commands
+                    .entity(*tile)
+                    .with_child((
+                        TileFlash(Timer::from_seconds(FLASH_SECS, TimerMode::Once)),
+                        Sprite::from_color(color.with_alpha(0.8), config.tile_size),
+                        Transform::from_xyz(0., 0., 1.),
+                    ));
+            }
+        }
+
+        let target = resolved.target.and_then(|target| enemies.get(target).ok());
+        if let (ActionKind::Combine, Some(enemy)) = (resolved.kind, target) {
+            let from = card_transform.translation() - view_transform.translation();
+            let to = enemy.translation() - view_transform.translation();
+
+            commands
+                .entity(game_view)
+                .with_child((
+                    Projectile {
+                        from: from.with_z(20.),
+                        to: to.with_z(20.),
+                        timer: Timer::from_seconds(PROJECTILE_SECS, TimerMode::Once),
+                    },
+                    Sprite::from_color(color, Vec2::splat(12.)),
+                    Transform::from_translation(from.with_z(20.)),
+                ));
+        }
+    }
+}
+
+/// Lifts the card and pulses its glow, both peak halfway through the timer.
+fn lift_cards(
+    mut commands: Commands,
+    time: Res<Time>,
+    speed: Res<GameSpeed>,
+    mut cards: Query<(Entity, &mut CardLift, &mut Transform, &Children)>,
+    mut glows: Query<(Entity, &mut Sprite), With<CardGlow>>,
+) {
+    for (card, mut lift, mut transform, children) in &mut cards {
+        lift.0.tick(time.delta().mul_f32(speed.0));
+        let pulse = (PI * lift.0.fraction()).sin();
+
+        transform.translation.y = LIFT_HEIGHT * pulse;
+
+        let mut card_glows = glows.iter_many_mut(children);
+        while let Some((glow, mut sprite)) = card_glows.fetch_next() {
+            if lift.0.is_finished() {
+                commands.entity(glow).despawn();
+            } else {
+                sprite.color.set_alpha(0.6 * pulse);
+            }
+        }
+
+        if lift.0.is_finished() {
+            transform.translation.y = 0.;
+            commands.entity(card).try_remove::<CardLift>();
+        }
+    }
+}
+
+fn flash_tiles(
+    mut commands: Commands,
+    time: Res<Time>,
+    speed: Res<GameSpeed>,
+    flashes: Query<(Entity, &mut TileFlash, &mut Sprite)>,
+) {
+    for (entity, mut flash, mut sprite) in flashes {
+        flash.0.tick(time.delta().mul_f32(speed.0));
+        if flash.0.is_finished() {
+            commands.entity(entity).despawn();
+        } else {
+            sprite.color.set_alpha(0.8 * flash.0.fraction_remaining());
+        }
+    }
+}
+
+fn move_projectiles(
+    mut commands: Commands,
+    time: Res<Time>,
+    speed: Res<GameSpeed>,
+    projectiles: Query<(Entity, &mut Projectile, &mut Transform)>,
+) {
+    for (entity, mut projectile, mut transform) in projectiles {
+        projectile.timer.tick(time.delta().mul_f32(speed.0));
+        if projectile.timer.is_finished() {
+            commands.entity(entity).despawn();
+        } else {
+            transform.translation = projectile.from.lerp(projectile.to, projectile.timer.fraction());
+        }
+    }
+}
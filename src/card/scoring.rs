@@ -0,0 +1,73 @@
+use bevy::prelude::*;
+
+/// Converts the number of matched requirement tiles into card points.
+#[derive(Component, Clone, PartialEq, Debug, Default)]
+pub enum CardScoring {
+    /// One point per matched tile.
+    #[default]
+    Linear,
+    /// One point per tile, but only once every tile matches.
+    AllOrNothing,
+    /// `(matched, points)` tiers, the highest reached tier wins.
+    Thresholds(Vec<(u64, u64)>),
+    /// One point per matched tile, plus a bonus once every tile matches.
+    BonusOnComplete(u64),
+}
+
+impl CardScoring {
+    pub fn points(&self, matched: u64, total: u64) -> u64 {
+        let is_complete = total > 0 && matched >= total;
+        match self {
+            CardScoring::Linear => matched,
+            CardScoring::AllOrNothing if is_complete => matched,
+            CardScoring::AllOrNothing => 0,
+            CardScoring::Thresholds(tiers) => tiers
+                .iter()
+                .filter(|(required, _)| matched >= *required)
+                .map(|(_, points)| *points)
+                .max()
+                .unwrap_or(0),
+            CardScoring::BonusOnComplete(bonus) if is_complete => matched + bonus,
+            CardScoring::BonusOnComplete(_) => matched,
+        }
+    }
+
+    /// Points once every tile matches.
+    pub fn max_points(&self, total: u64) -> u64 {
+        self.points(total, total)
+    }
+
+    /// Short description displayed on the card face.
+    pub fn label(&self, total: u64) -> String {
+        match self {
+            CardScoring::Linear => "1 per tile".into(),
+            CardScoring::AllOrNothing => format!("{}/{} or nothing", total, total),
+            CardScoring::Thresholds(tiers) => tiers
+                .iter()
+                .map(|(required, points)| format!("{}/{}: {}", required, total, points))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            CardScoring::BonusOnComplete(bonus) => format!("+{} on {}/{}", bonus, total, total),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_points() {
+        assert_eq!(3, CardScoring::Linear.points(3, 5));
+        assert_eq!(0, CardScoring::AllOrNothing.points(4, 5));
+        assert_eq!(5, CardScoring::AllOrNothing.points(5, 5));
+
+        let tiers = CardScoring::Thresholds(vec![(3, 4), (5, 12)]);
+        assert_eq!(0, tiers.points(2, 5));
+        assert_eq!(4, tiers.points(4, 5));
+        assert_eq!(12, tiers.points(5, 5));
+
+        assert_eq!(1, CardScoring::BonusOnComplete(2).points(1, 2));
+        assert_eq!(4, CardScoring::BonusOnComplete(2).points(2, 2));
+    }
+}
@@ -2,23 +2,142 @@ use std::collections::VecDeque;
 
 use bevy::prelude::*;
 
-pub enum Task {
-    DealDamage {
-        damage: u32,
+/// Deferred effect, executed once the previous task in the queue has finished.
+pub struct Task {
+    /// Tasks with lower order run first, tasks with equal order run in insertion order.
+    order: u64,
+    run: Box<dyn FnOnce(&mut World) + Send + Sync>,
+}
+
+impl Task {
+    pub fn write_message<M: Message>(order: u64, message: M) -> Self {
+        Task {
+            order,
+            run: Box::new(move |world: &mut World| {
+                world.write_message(message);
+            }),
+        }
+    }
+
+    pub fn order(&self) -> u64 {
+        self.order
+    }
+
+    /// Task that does nothing, used to wait before the next one.
+    pub fn wait(order: u64) -> Self {
+        Task {
+            order,
+            run: Box::new(|_: &mut World| {}),
+        }
     }
 }
 
+/// Written once the last task of the screen's queue has been executed.
+#[derive(Message)]
+pub struct TasksFinished(pub Entity);
+
 pub struct TaskPlugin;
 
-#[derive(Resource)]
+impl Plugin for TaskPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_message::<TasksFinished>()
+            .add_systems(Update, execute_task);
+    }
+}
+
+/// Sequenced queue of effects, each task waits for its own timer
+/// only after the previous task has been executed.
+/// Lives on the screen entity, so despawning the screen drops its pending tasks.
+#[derive(Component)]
 pub struct Tasks {
     tasks: VecDeque<(Timer, Task)>,
+    paused: bool,
+    skipping: bool,
+    speed: f32,
+}
+
+impl Default for Tasks {
+    fn default() -> Self {
+        Tasks {
+            tasks: VecDeque::new(),
+            paused: false,
+            skipping: false,
+            speed: 1.0,
+        }
+    }
+}
+
+impl Tasks {
+    /// Queues the task to run `delay` seconds after the task preceding it.
+    pub fn insert(&mut self, delay: f32, task: Task) {
+        let position = self.tasks
+            .iter()
+            .position(|(_, queued)| queued.order > task.order)
+            .unwrap_or(self.tasks.len());
+
+        self.tasks.insert(position, (Timer::from_seconds(delay, TimerMode::Once), task));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    /// Executes all queued tasks with the next update, preserving their order.
+    pub fn skip(&mut self) {
+        self.skipping = true;
+    }
+
+    /// Multiplier applied to the task timers, 2.0 runs the queue twice as fast.
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed.max(0.);
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
 }
 
 fn execute_task(
+    mut commands: Commands,
     time: Res<Time>,
-    tasks: ResMut<Tasks>,
+    mut screens: Query<(Entity, &mut Tasks)>,
+    mut writer: MessageWriter<TasksFinished>,
 ) {
+    for (screen, mut tasks) in &mut screens {
+        let tasks = &mut *tasks;
+        if tasks.is_empty() || (tasks.paused && !tasks.skipping) {
+            continue
+        }
 
-}
+        let mut delta = time.delta().mul_f32(tasks.speed);
+        while let Some((timer, _)) = tasks.tasks.front_mut() {
+            if !tasks.skipping {
+                // time left after the timer has finished carries over to the next task
+                let remaining = timer.remaining();
+                timer.tick(delta);
+                if !timer.is_finished() {
+                    break
+                }
+                delta = delta.saturating_sub(remaining);
+            }
+
+            if let Some((_, task)) = tasks.tasks.pop_front() {
+                commands.queue(task.run);
+            }
+        }
 
+        if tasks.is_empty() {
+            tasks.skipping = false;
+            writer.write(TasksFinished(screen));
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use std::{fs::File, io::{BufWriter, Write}};
+
+use bevy::{input::mouse::MouseWheel, prelude::*, sprite::Anchor, text::TextLayout};
+use serde::Serialize;
+
+use crate::{card::actions::ActionSource, core::prelude::*};
+
+/// File the combat log is exported to.
+const EXPORT_PATH: &str = "combat_log.jsonl";
+
+/// Number of records visible in the panel at once.
+const VISIBLE_RECORDS: usize = 12;
+
+/// Result of a resolved action, written by the systems applying it.
+#[derive(Message, Clone, Serialize)]
+pub struct CombatLogEntry {
+    pub card: String,
+    pub action: &'static str,
+    pub amount: u64,
+    pub matched: u64,
+    pub total: u64,
+    pub points: u64,
+    pub modifiers: Vec<String>,
+    pub health: Option<u64>,
+}
+
+impl CombatLogEntry {
+    pub fn new(source: &ActionSource, action: &'static str, amount: u64) -> Self {
+        CombatLogEntry {
+            card: source.card_name.clone(),
+            action,
+            amount,
+            matched: source.matched,
+            total: source.total,
+            points: source.points,
+            modifiers: vec![],
+            health: None,
+        }
+    }
+
+    /// Entry for an action of an enemy, which has no card requirements.
+    pub fn from_enemy(name: &str, action: &'static str, amount: u64) -> Self {
+        CombatLogEntry::named(name, action, amount)
+    }
+
+    /// Entry without a card, e.g. for the player or status effects.
+    pub fn named(name: &str, action: &'static str, amount: u64) -> Self {
+        CombatLogEntry {
+            card: name.to_string(),
+            action,
+            amount,
+            matched: 0,
+            total: 0,
+            points: 0,
+            modifiers: vec![],
+            health: None,
+        }
+    }
+
+    /// Health of the target after the action has been applied.
+    pub fn with_health(mut self, health: u64) -> Self {
+        self.health = Some(health);
+        self
+    }
+
+    pub fn with_modifier(mut self, modifier: String) -> Self {
+        self.modifiers.push(modifier);
+        self
+    }
+}
+
+#[derive(Serialize)]
+pub struct CombatLogRecord {
+    /// Seconds since the game started.
+    pub time: f32,
+    #[serde(flatten)]
+    pub entry: CombatLogEntry,
+}
+
+impl CombatLogRecord {
+    fn line(&self) -> String {
+        let entry = &self.entry;
+        let mut line = format!("{} {} {} ({}/{})", entry.card, entry.action, entry.amount, entry.matched, entry.total);
+        if !entry.modifiers.is_empty() {
+            line += &format!(" [{}]", entry.modifiers.join(", "));
+        }
+        if let Some(health) = entry.health {
+            line += &format!(" -> {}", health);
+        }
+        line
+    }
+}
+
+/// Single log component, kept for the whole session.
+#[derive(Component, Default)]
+pub struct CombatLog {
+    pub records: Vec<CombatLogRecord>,
+}
+
+/// Panel displaying the combat log, scrolled with the mouse wheel.
+#[derive(Component)]
+pub struct CombatLogView;
+
+/// Number of records scrolled back from the newest one.
+#[derive(Component, Default, PartialEq)]
+struct CombatLogScroll(usize);
+
+#[derive(Message, Default)]
+pub struct ExportCombatLog;
+
+pub struct CombatLogPlugin;
+
+impl Plugin for CombatLogPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_message::<CombatLogEntry>()
+            .add_message::<ExportCombatLog>()
+            .add_systems(Startup, setup_combat_log)
+            .add_systems(Update, setup_combat_log_view)
+            .add_systems(Update, record_combat_log.run_if(on_message::<CombatLogEntry>))
+            .add_systems(Update, scroll_combat_log.run_if(on_message::<MouseWheel>))
+            .add_systems(Update, display_combat_log.after(record_combat_log).after(scroll_combat_log))
+            .add_systems(Update, action_message::<ExportCombatLog>.run_if(action_just_pressed(InputAction::ExportLog)))
+            .add_systems(Update, export_combat_log.run_if(on_message::<ExportCombatLog>));
+    }
+}
+
+fn setup_combat_log(
+    mut commands: Commands,
+) {
+    commands.spawn((
+        Name::new("Combat Log"),
+        CombatLog::default(),
+    ));
+}
+
+fn setup_combat_log_view(
+    mut commands: Commands,
+    views: Query<Entity, Added<CombatLogView>>,
+) {
+    views
+        .into_iter()
+        .for_each(|view| {
+            commands
+                .entity(view)
+                .try_insert((
+                    Text2d::new(""),
+                    TextFont {
+                        font_size: 12.,
+                        ..default()
+                    },
+                    TextLayout::new_with_justify(Justify::Left),
+                    Anchor::TOP_LEFT,
+                    TouchArea {
+                        area: Vec2::new(200., 240.),
+                    },
+                    CombatLogScroll(0),
+                ));
+        });
+}
+
+fn record_combat_log(
+    time: Res<Time>,
+    mut log: Single<&mut CombatLog>,
+    mut reader: MessageReader<CombatLogEntry>,
+) {
+    for entry in reader.read() {
+        log.records.push(CombatLogRecord {
+            time: time.elapsed_secs(),
+            entry: entry.clone(),
+        });
+    }
+}
+
+fn scroll_combat_log(
+    log: Single<&CombatLog>,
+    views: Query<(&TouchState, &mut CombatLogScroll), With<CombatLogView>>,
+    mut reader: MessageReader<MouseWheel>,
+) {
+    let lines: f32 = reader.read().map(|wheel| wheel.y).sum();
+    let max_scroll = log.records.len().saturating_sub(VISIBLE_RECORDS);
+
+    for (state, mut scroll) in views {
+        if !state.is_touching() {
+            continue
+        }
+
+        let value = (scroll.0 as f32 + lines.round()).clamp(0., max_scroll as f32) as usize;
+        scroll.set_if_neq(CombatLogScroll(value));
+    }
+}
+
+fn display_combat_log(
+    log: Single<Ref<CombatLog>>,
+    views: Query<(Ref<CombatLogScroll>, &mut Text2d), With<CombatLogView>>,
+) {
+    for (scroll, mut text) in views {
+        if !log.is_changed() && !scroll.is_changed() {
+            continue
+        }
+
+        let end = log.records.len().saturating_sub(scroll.0);
+        let start = end.saturating_sub(VISIBLE_RECORDS);
+        let lines: Vec<_> = log.records[start..end]
+            .iter()
+            .map(|record| record.line())
+            .collect();
+
+        *text = Text2d::new(lines.join("\n"));
+    }
+}
+
+/// Writes every record as a single JSON line.
+fn export_combat_log(
+    log: Single<&CombatLog>,
+) {
+    let result = File::create(EXPORT_PATH)
+        .and_then(|file| {
+            let mut writer = BufWriter::new(file);
+            for record in &log.records {
+                let line = serde_json::to_string(record)?;
+                writeln!(writer, "{}", line)?;
+            }
+            writer.flush()
+        });
+
+    match result {
+        Ok(()) => info!("exported {} combat log records to {}", log.records.len(), EXPORT_PATH),
+        Err(err) => warn!("failed to export combat log: {}", err),
+    }
+}
@@ -0,0 +1,207 @@
+use std::fs;
+
+use bevy::{input::InputSystems, platform::collections::{HashMap, HashSet}, prelude::*};
+use serde::{Deserialize, Serialize};
+
+/// User bindings, created with the defaults when missing.
+const INPUT_MAP_PATH: &str = "input.ron";
+
+pub struct InputMapPlugin;
+
+impl Plugin for InputMapPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(InputMap::load())
+            .init_resource::<ActionState>()
+            .add_systems(PreUpdate, update_action_state.after(InputSystems))
+            .add_systems(Last, save_input_map.run_if(resource_changed::<InputMap>.and(not(resource_added::<InputMap>))));
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum InputAction {
+    Pick,
+    Cancel,
+    Cast,
+    Redraw,
+    Refresh,
+    Undo,
+    ToggleInspector,
+    /// Runs the remaining actions of the phase at once.
+    Skip,
+    ExportLog,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InputBinding {
+    Mouse(MouseButton),
+    Key(KeyCode),
+    Gamepad(GamepadButton),
+}
+
+/// Bindings per action, any of them triggers the action.
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct InputMap(pub HashMap<InputAction, Vec<InputBinding>>);
+
+impl Default for InputMap {
+    fn default() -> Self {
+        use InputBinding::*;
+
+        InputMap(HashMap::from_iter([
+            (InputAction::Pick, vec![Mouse(MouseButton::Left)]),
+            (InputAction::Cancel, vec![Mouse(MouseButton::Right), Gamepad(GamepadButton::East)]),
+            (InputAction::Cast, vec![Key(KeyCode::Space), Gamepad(GamepadButton::South)]),
+            (InputAction::Redraw, vec![Key(KeyCode::KeyR), Gamepad(GamepadButton::North)]),
+            (InputAction::Refresh, vec![Key(KeyCode::KeyF), Gamepad(GamepadButton::West)]),
+            (InputAction::Undo, vec![Key(KeyCode::KeyZ), Key(KeyCode::Backspace), Gamepad(GamepadButton::LeftTrigger)]),
+            (InputAction::ToggleInspector, vec![Key(KeyCode::Escape), Gamepad(GamepadButton::Select)]),
+            (InputAction::Skip, vec![Key(KeyCode::Space), Gamepad(GamepadButton::South)]),
+            (InputAction::ExportLog, vec![Key(KeyCode::F2)]),
+        ]))
+    }
+}
+
+impl InputMap {
+    /// Reads the user config, falling back to the defaults for missing or broken files
+    /// and for actions the file does not bind.
+    fn load() -> Self {
+        let Ok(text) = fs::read_to_string(INPUT_MAP_PATH) else {
+            let map = InputMap::default();
+            map.save();
+            return map
+        };
+
+        match ron::de::from_str::<InputMap>(&text) {
+            Ok(mut map) => {
+                for (action, bindings) in InputMap::default().0 {
+                    map.0.entry(action).or_insert(bindings);
+                }
+                map
+            },
+            Err(err) => {
+                warn!("failed to read {}: {}", INPUT_MAP_PATH, err);
+                InputMap::default()
+            }
+        }
+    }
+
+    fn save(&self) {
+        let result = ron::ser::to_string_pretty(self, default())
+            .map_err(|err| err.to_string())
+            .and_then(|text| fs::write(INPUT_MAP_PATH, text).map_err(|err| err.to_string()));
+
+        if let Err(err) = result {
+            error!("failed to save {}: {}", INPUT_MAP_PATH, err);
+        }
+    }
+}
+
+/// Actions triggered this frame, read by game systems instead of raw inputs.
+#[derive(Resource, Default, Debug)]
+pub struct ActionState {
+    pressed: HashSet<InputAction>,
+    just_pressed: HashSet<InputAction>,
+    just_released: HashSet<InputAction>,
+}
+
+impl ActionState {
+    pub fn pressed(&self, action: InputAction) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: InputAction) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    pub fn just_released(&self, action: InputAction) -> bool {
+        self.just_released.contains(&action)
+    }
+}
+
+fn update_action_state(
+    map: Res<InputMap>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut state: ResMut<ActionState>,
+) {
+    let was_pressed = std::mem::take(&mut state.pressed);
+    state.just_pressed.clear();
+    state.just_released.clear();
+
+    for (&action, bindings) in &map.0 {
+        let mut pressed = false;
+        let mut just_pressed = false;
+        for binding in bindings {
+            let (down, started) = match *binding {
+                InputBinding::Mouse(button) => (mouse.pressed(button), mouse.just_pressed(button)),
+                InputBinding::Key(key) => (keys.pressed(key), keys.just_pressed(key)),
+                InputBinding::Gamepad(button) => (
+                    gamepads.iter().any(|gamepad| gamepad.pressed(button)),
+                    gamepads.iter().any(|gamepad| gamepad.just_pressed(button)),
+                ),
+            };
+            pressed |= down;
+            just_pressed |= started;
+        }
+
+        if pressed {
+            state.pressed.insert(action);
+        }
+        if just_pressed {
+            state.just_pressed.insert(action);
+        }
+        if !pressed && was_pressed.contains(&action) {
+            state.just_released.insert(action);
+        }
+    }
+}
+
+fn save_input_map(
+    map: Res<InputMap>,
+) {
+    map.save();
+}
+
+pub fn action_just_pressed(action: InputAction) -> impl FnMut(Res<ActionState>) -> bool + Clone {
+    move |state: Res<ActionState>| state.just_pressed(action)
+}
+
+pub fn action_pressed(action: InputAction) -> impl FnMut(Res<ActionState>) -> bool + Clone {
+    move |state: Res<ActionState>| state.pressed(action)
+}
+
+pub fn action_just_released(action: InputAction) -> impl FnMut(Res<ActionState>) -> bool + Clone {
+    move |state: Res<ActionState>| state.just_released(action)
+}
+
+/// Flips between active and inactive every time the action is pressed.
+pub fn action_toggle_active(default: bool, action: InputAction) -> impl FnMut(Res<ActionState>) -> bool + Clone {
+    let mut active = default;
+    move |state: Res<ActionState>| {
+        active ^= state.just_pressed(action);
+        active
+    }
+}
+
+/// Writes the message, run with `action_just_pressed` like `button_system` for buttons.
+pub fn action_message<M: Message + Default>(
+    mut writer: MessageWriter<M>,
+) {
+    writer.write(M::default());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_input_map_round_trip() {
+        let map = InputMap::default();
+        let text = ron::ser::to_string_pretty(&map, default()).unwrap();
+        let parsed: InputMap = ron::de::from_str(&text).unwrap();
+
+        assert_eq!(parsed, map);
+        assert_eq!(parsed.0[&InputAction::Pick], vec![InputBinding::Mouse(MouseButton::Left)]);
+    }
+}
@@ -0,0 +1,176 @@
+use bevy::{picking::events::{DragEnd, Pointer}, prelude::*};
+
+use super::press::{self, PressState};
+
+pub struct GesturePlugin;
+
+/// Entities that recognize gestures, they also need a `PressArea`.
+#[derive(Component)]
+pub struct GestureArea;
+
+/// Thresholds used to tell the gestures apart.
+#[derive(Resource, Clone)]
+pub struct GestureConfig {
+    /// Longer presses are not taps.
+    pub tap_max_secs: f32,
+    /// Maximum time between the two taps of a double tap.
+    pub double_tap_secs: f32,
+    pub long_press_secs: f32,
+    /// Minimum drag distance in screen pixels.
+    pub swipe_min_distance: f32,
+}
+
+impl Default for GestureConfig {
+    fn default() -> Self {
+        GestureConfig {
+            tap_max_secs: 0.25,
+            double_tap_secs: 0.3,
+            long_press_secs: 0.5,
+            swipe_min_distance: 24.,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SwipeDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl SwipeDirection {
+    /// Direction of the longer axis of a drag in screen space, where y points down.
+    fn from_screen(distance: Vec2) -> Self {
+        if distance.x.abs() >= distance.y.abs() {
+            if distance.x < 0. { SwipeDirection::Left } else { SwipeDirection::Right }
+        } else if distance.y < 0. {
+            SwipeDirection::Up
+        } else {
+            SwipeDirection::Down
+        }
+    }
+}
+
+#[derive(Message)]
+pub struct Tap {
+    pub entity: Entity,
+}
+
+/// Written on the second tap, after its `Tap`.
+#[derive(Message)]
+pub struct DoubleTap {
+    pub entity: Entity,
+}
+
+/// Written once per press, the release is then not a tap.
+#[derive(Message)]
+pub struct LongPress {
+    pub entity: Entity,
+}
+
+#[derive(Message)]
+pub struct Swipe {
+    pub entity: Entity,
+    pub direction: SwipeDirection,
+}
+
+#[derive(Component, Default)]
+struct GestureTracker {
+    pressed_for: f32,
+    long_pressed: bool,
+    last_tap: Option<f32>,
+}
+
+impl Plugin for GesturePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<GestureConfig>()
+            .add_message::<Tap>()
+            .add_message::<DoubleTap>()
+            .add_message::<LongPress>()
+            .add_message::<Swipe>()
+            .add_observer(detect_swipe)
+            .add_systems(Update, add_tracker)
+            .add_systems(PreUpdate, detect_gestures.after(press::detect_press));
+    }
+}
+
+fn add_tracker(
+    mut commands: Commands,
+    entities: Query<Entity, Added<GestureArea>>,
+) {
+    for entity in &entities {
+        commands.entity(entity).try_insert(GestureTracker::default());
+    }
+}
+
+fn detect_gestures(
+    time: Res<Time>,
+    config: Res<GestureConfig>,
+    entities: Query<(Entity, &PressState, &mut GestureTracker), Changed<PressState>>,
+    mut taps: MessageWriter<Tap>,
+    mut double_taps: MessageWriter<DoubleTap>,
+    mut long_presses: MessageWriter<LongPress>,
+) {
+    let now = time.elapsed_secs();
+    for (entity, state, mut tracker) in entities {
+        match state {
+            PressState::JustPressed => {
+                tracker.pressed_for = 0.;
+                tracker.long_pressed = false;
+            },
+            PressState::Pressed { duration } => {
+                tracker.pressed_for = *duration;
+                if *duration >= config.long_press_secs && !tracker.long_pressed {
+                    tracker.long_pressed = true;
+                    long_presses.write(LongPress { entity });
+                }
+            },
+            PressState::JustReleased if !tracker.long_pressed && tracker.pressed_for <= config.tap_max_secs => {
+                taps.write(Tap { entity });
+                match tracker.last_tap {
+                    Some(last) if now - last <= config.double_tap_secs => {
+                        double_taps.write(DoubleTap { entity });
+                        tracker.last_tap = None;
+                    },
+                    _ => tracker.last_tap = Some(now),
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+/// Drags end on the entity they started on, wherever the pointer is released.
+fn detect_swipe(
+    mut drag: On<Pointer<DragEnd>>,
+    config: Res<GestureConfig>,
+    areas: Query<(), With<GestureArea>>,
+    mut writer: MessageWriter<Swipe>,
+) {
+    if drag.button != PointerButton::Primary || !areas.contains(drag.entity) {
+        return
+    }
+
+    drag.propagate(false);
+    if drag.distance.length() >= config.swipe_min_distance {
+        writer.write(Swipe {
+            entity: drag.entity,
+            direction: SwipeDirection::from_screen(drag.distance),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_swipe_direction() {
+        assert_eq!(SwipeDirection::Right, SwipeDirection::from_screen(Vec2::new(30., 10.)));
+        assert_eq!(SwipeDirection::Left, SwipeDirection::from_screen(Vec2::new(-30., -10.)));
+        assert_eq!(SwipeDirection::Up, SwipeDirection::from_screen(Vec2::new(5., -30.)));
+        assert_eq!(SwipeDirection::Down, SwipeDirection::from_screen(Vec2::new(-5., 30.)));
+    }
+}
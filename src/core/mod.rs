@@ -1,10 +1,16 @@
+mod action;
+mod gesture;
 mod press;
+mod recording;
 mod mouse;
 mod touch;
 
 pub mod prelude {
+    pub use super::action::{InputMapPlugin, InputAction, action_just_pressed, action_pressed, action_just_released, action_toggle_active, action_message};
     pub use super::mouse::{MousePlugin, MousePosition};
-    pub use super::touch::{TouchPlugin, TouchArea, TouchState, just_touched};
+    pub use super::touch::{TouchPlugin, TouchArea, TouchPassThrough, TouchState, just_touched};
     pub use super::press::{PressPlugin, PressArea, PressState};
+    pub use super::recording::InputRecordingPlugin;
+    pub use super::gesture::{GesturePlugin, GestureArea, Tap, DoubleTap, LongPress, Swipe, SwipeDirection};
 }
 
@@ -68,14 +68,15 @@ fn on_mouse_move(
     *state = MouseState::Moving;
 }
 
+/// Follows the cursor messages rather than the window, so recorded input can be played back.
 pub fn update_mouse_position(
-    window: Single<&Window>,
     camera: Single<(&Camera, &GlobalTransform)>,
+    mut cursor_moves: MessageReader<CursorMoved>,
     mut mouse: ResMut<MousePosition>,
 ) {
     let (camera, camera_transform) = *camera;
-    let position = window.cursor_position()
-        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor).ok());
+    let position = cursor_moves.read().last()
+        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor.position).ok());
 
     if let Some(position) = position {
         mouse.set_if_neq(MousePosition(position));
@@ -1,6 +1,6 @@
 use bevy::prelude::*;
 
-use super::touch::{self, TouchState};
+use super::touch::{self, PointerInteraction, TouchState};
 
 pub struct PressPlugin;
 
@@ -49,15 +49,14 @@ fn add_state(
         });
 }
 
-fn detect_press(
-    mbi: Res<ButtonInput<MouseButton>>,
+pub fn detect_press(
     time: Res<Time>,
-    entities: Query<(Entity, &TouchState, &mut PressState), Changed<TouchState>>,
+    entities: Query<(Entity, &TouchState, &PointerInteraction, &mut PressState), Changed<TouchState>>,
 ) {
     entities
         .into_iter()
-        .for_each(|(_, touch_state, mut press_state)| {
-            match (touch_state.is_touching(), &mut *press_state, mbi.pressed(MouseButton::Left) || mbi.just_pressed(MouseButton::Left)) {
+        .for_each(|(_, touch_state, interaction, mut press_state)| {
+            match (touch_state.is_touching(), &mut *press_state, interaction.pressed) {
                 (false, PressState::None, _) |
                 (true, PressState::None, false) => {
                     // outside
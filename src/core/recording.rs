@@ -0,0 +1,378 @@
+use std::{collections::VecDeque, fs::{self, File}, io::{BufWriter, Write}, path::PathBuf, time::Duration};
+
+use bevy::{diagnostic::FrameCount, ecs::system::SystemParam, input::{gamepad::{GamepadConnection, GamepadConnectionEvent, RawGamepadButtonChangedEvent, RawGamepadEvent}, keyboard::{Key, KeyboardInput, NativeKey}, mouse::MouseButtonInput, touch::{TouchInput, TouchPhase}, ButtonState, InputSystems}, prelude::*, time::{TimeSystems, TimeUpdateStrategy}, window::{PrimaryWindow, WindowEvent}};
+use serde::{Deserialize, Serialize};
+
+/// Records input with `--record <path>` or replays it with `--playback <path>`.
+pub struct InputRecordingPlugin {
+    mode: RecordingMode,
+    seed: u64,
+    /// Failure to load the playback, logged on startup as the log plugin does not exist yet.
+    error: Option<String>,
+}
+
+enum RecordingMode {
+    Off,
+    Record(PathBuf),
+    Playback(Recording),
+}
+
+impl InputRecordingPlugin {
+    pub fn from_args() -> Self {
+        let args: Vec<String> = std::env::args().collect();
+        let path = |flag: &str| args
+            .iter()
+            .position(|arg| arg == flag)
+            .and_then(|i| args.get(i + 1))
+            .map(PathBuf::from);
+
+        let mut error = None;
+        if let Some(path) = path("--playback") {
+            match Recording::load(&path) {
+                Ok(recording) => {
+                    return InputRecordingPlugin {
+                        seed: recording.header.seed,
+                        mode: RecordingMode::Playback(recording),
+                        error,
+                    }
+                },
+                Err(err) => error = Some(format!("failed to load recording {}: {}", path.display(), err)),
+            }
+        }
+
+        InputRecordingPlugin {
+            mode: path("--record").map_or(RecordingMode::Off, RecordingMode::Record),
+            seed: rand::random(),
+            error,
+        }
+    }
+
+    /// Seed of the run, a playback uses the recorded one.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl Plugin for InputRecordingPlugin {
+    fn build(&self, app: &mut App) {
+        if let Some(error) = self.error.clone() {
+            app.add_systems(Startup, move || error!("{}", error));
+        }
+
+        match &self.mode {
+            RecordingMode::Off => {},
+            RecordingMode::Record(path) => {
+                match InputRecorder::create(path, self.seed) {
+                    Ok(recorder) => {
+                        app
+                            .insert_resource(recorder)
+                            .add_systems(PreUpdate, record_input.after(InputSystems));
+                    },
+                    Err(err) => {
+                        let error = format!("failed to create recording {}: {}", path.display(), err);
+                        app.add_systems(Startup, move || error!("{}", error));
+                    },
+                }
+            },
+            RecordingMode::Playback(recording) => {
+                info!("playing back {} input frames", recording.frames.len());
+                app
+                    .insert_resource(InputPlayback {
+                        frames: recording.frames.clone().into(),
+                        previous: (0, 0.),
+                        gamepad: None,
+                    })
+                    .add_systems(First, pace_playback
+                        .run_if(resource_exists::<InputPlayback>)
+                        .before(TimeSystems)
+                    )
+                    .add_systems(PreUpdate, play_back_input
+                        .run_if(resource_exists::<InputPlayback>)
+                        .before(InputSystems)
+                    );
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+struct RecordingHeader {
+    seed: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+enum RecordedInput {
+    /// Cursor position in logical window pixels, drives `MousePosition` and picking.
+    CursorMoved(Vec2),
+    Mouse(MouseButton, ButtonState),
+    Key(KeyCode, ButtonState),
+    /// Touch position in logical window pixels, `id` tells fingers apart.
+    Touch {
+        id: u64,
+        phase: TouchPhase,
+        position: Vec2,
+    },
+    /// Raw button value, the gamepad settings turn it into presses on playback.
+    Gamepad(GamepadButton, f32),
+}
+
+/// Inputs of one frame, in the order the window received them.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+struct RecordedFrame {
+    frame: u32,
+    /// Real time since startup, playback paces frames to match it.
+    elapsed: f32,
+    inputs: Vec<RecordedInput>,
+}
+
+/// A header line followed by one JSON line per frame with input.
+#[derive(Clone, PartialEq, Debug)]
+struct Recording {
+    header: RecordingHeader,
+    frames: Vec<RecordedFrame>,
+}
+
+impl Recording {
+    fn load(path: &PathBuf) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|err| err.to_string())?;
+        Recording::parse(&text)
+    }
+
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+        let header = lines
+            .next()
+            .ok_or("empty recording")?;
+
+        Ok(Recording {
+            header: serde_json::from_str(header).map_err(|err| err.to_string())?,
+            frames: lines
+                .map(serde_json::from_str)
+                .collect::<Result<_, _>>()
+                .map_err(|err| err.to_string())?,
+        })
+    }
+}
+
+#[derive(Resource)]
+struct InputRecorder {
+    writer: BufWriter<File>,
+}
+
+impl InputRecorder {
+    fn create(path: &PathBuf, seed: u64) -> std::io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "{}", serde_json::to_string(&RecordingHeader { seed })?)?;
+        writer.flush()?;
+
+        info!("recording input to {} with seed {}", path.display(), seed);
+        Ok(InputRecorder { writer })
+    }
+}
+
+#[derive(Resource)]
+struct InputPlayback {
+    frames: VecDeque<RecordedFrame>,
+    /// Frame and elapsed time of the last played back frame.
+    previous: (u32, f32),
+    /// Stand-in for the recorded gamepads, connected with the first gamepad input.
+    gamepad: Option<Entity>,
+}
+
+/// Written line by line so a crash still leaves a usable recording.
+fn record_input(
+    frame: Res<FrameCount>,
+    time: Res<Time<Real>>,
+    mut recorder: ResMut<InputRecorder>,
+    mut window_events: MessageReader<WindowEvent>,
+    mut gamepad_buttons: MessageReader<RawGamepadButtonChangedEvent>,
+) {
+    let inputs: Vec<_> = window_events
+        .read()
+        .filter_map(|event| match event {
+            WindowEvent::CursorMoved(moved) => Some(RecordedInput::CursorMoved(moved.position)),
+            WindowEvent::MouseButtonInput(input) => Some(RecordedInput::Mouse(input.button, input.state)),
+            WindowEvent::KeyboardInput(input) if !input.repeat => Some(RecordedInput::Key(input.key_code, input.state)),
+            WindowEvent::TouchInput(touch) => Some(RecordedInput::Touch {
+                id: touch.id,
+                phase: touch.phase,
+                position: touch.position,
+            }),
+            _ => None,
+        })
+        .chain(gamepad_buttons
+            .read()
+            .map(|changed| RecordedInput::Gamepad(changed.button, changed.value))
+        )
+        .collect();
+
+    if inputs.is_empty() {
+        return
+    }
+
+    let recorded = RecordedFrame {
+        frame: frame.0,
+        elapsed: time.elapsed_secs(),
+        inputs,
+    };
+
+    let result = serde_json::to_string(&recorded)
+        .map_err(std::io::Error::from)
+        .and_then(|line| writeln!(recorder.writer, "{}", line))
+        .and_then(|_| recorder.writer.flush());
+
+    if let Err(err) = result {
+        warn!("failed to record input: {}", err);
+    }
+}
+
+/// Spreads the recorded time evenly over the frames up to the next recorded frame.
+fn pace_playback(
+    playback: Res<InputPlayback>,
+    mut strategy: ResMut<TimeUpdateStrategy>,
+) {
+    let Some(next) = playback.frames.front() else {
+        return
+    };
+
+    let (previous_frame, previous_elapsed) = playback.previous;
+    let frames = next.frame.saturating_sub(previous_frame).max(1);
+    let step = (next.elapsed - previous_elapsed).max(0.) / frames as f32;
+    *strategy = TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(step));
+}
+
+/// Input messages replaced during playback.
+#[derive(SystemParam)]
+struct InputMessages<'w> {
+    window_events: ResMut<'w, Messages<WindowEvent>>,
+    cursor_moves: ResMut<'w, Messages<CursorMoved>>,
+    mouse_buttons: ResMut<'w, Messages<MouseButtonInput>>,
+    keys: ResMut<'w, Messages<KeyboardInput>>,
+    touches: ResMut<'w, Messages<TouchInput>>,
+    gamepad_events: ResMut<'w, Messages<RawGamepadEvent>>,
+    gamepad_buttons: ResMut<'w, Messages<RawGamepadButtonChangedEvent>>,
+    gamepad_connections: ResMut<'w, Messages<GamepadConnectionEvent>>,
+}
+
+/// Replaces the real window and gamepad input with the recorded one, before `ButtonInput` and picking read it.
+fn play_back_input(
+    mut commands: Commands,
+    frame: Res<FrameCount>,
+    window: Single<Entity, With<PrimaryWindow>>,
+    mut playback: ResMut<InputPlayback>,
+    mut messages: InputMessages,
+    mut strategy: ResMut<TimeUpdateStrategy>,
+    mut cursor: Local<Option<Vec2>>,
+) {
+    messages.window_events.clear();
+    messages.cursor_moves.clear();
+    messages.mouse_buttons.clear();
+    messages.keys.clear();
+    messages.touches.clear();
+    messages.gamepad_events.clear();
+    messages.gamepad_buttons.clear();
+
+    while playback.frames.front().is_some_and(|next| next.frame <= frame.0) {
+        let Some(recorded) = playback.frames.pop_front() else {
+            break
+        };
+
+        for input in recorded.inputs {
+            match input {
+                RecordedInput::CursorMoved(position) => {
+                    let moved = CursorMoved {
+                        window: *window,
+                        position,
+                        delta: cursor.map(|last| position - last),
+                    };
+                    *cursor = Some(position);
+                    messages.window_events.write(moved.clone().into());
+                    messages.cursor_moves.write(moved);
+                },
+                RecordedInput::Mouse(button, state) => {
+                    let input = MouseButtonInput { button, state, window: *window };
+                    messages.window_events.write(input.into());
+                    messages.mouse_buttons.write(input);
+                },
+                RecordedInput::Key(key_code, state) => {
+                    let input = KeyboardInput {
+                        key_code,
+                        logical_key: Key::Unidentified(NativeKey::Unidentified),
+                        state,
+                        text: None,
+                        repeat: false,
+                        window: *window,
+                    };
+                    messages.window_events.write(input.clone().into());
+                    messages.keys.write(input);
+                },
+                RecordedInput::Touch { id, phase, position } => {
+                    let touch = TouchInput {
+                        phase,
+                        position,
+                        window: *window,
+                        force: None,
+                        id,
+                    };
+                    messages.window_events.write(touch.into());
+                    messages.touches.write(touch);
+                },
+                RecordedInput::Gamepad(button, value) => {
+                    let gamepad = *playback.gamepad.get_or_insert_with(|| {
+                        let gamepad = commands.spawn_empty().id();
+                        let connected = GamepadConnectionEvent::new(gamepad, GamepadConnection::Connected {
+                            name: "Playback".to_string(),
+                            vendor_id: None,
+                            product_id: None,
+                        });
+                        messages.gamepad_events.write(connected.clone().into());
+                        messages.gamepad_connections.write(connected);
+                        gamepad
+                    });
+
+                    let changed = RawGamepadButtonChangedEvent::new(gamepad, button, value);
+                    messages.gamepad_events.write(changed.into());
+                    messages.gamepad_buttons.write(changed);
+                },
+            }
+        }
+
+        playback.previous = (recorded.frame, recorded.elapsed);
+    }
+
+    if playback.frames.is_empty() {
+        info!("playback finished");
+        commands.remove_resource::<InputPlayback>();
+        *strategy = TimeUpdateStrategy::Automatic;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recording_round_trip() {
+        let frame = RecordedFrame {
+            frame: 12,
+            elapsed: 0.2,
+            inputs: vec![
+                RecordedInput::CursorMoved(Vec2::new(10., 20.)),
+                RecordedInput::Mouse(MouseButton::Left, ButtonState::Pressed),
+                RecordedInput::Key(KeyCode::Space, ButtonState::Released),
+                RecordedInput::Touch { id: 1, phase: TouchPhase::Started, position: Vec2::new(30., 40.) },
+                RecordedInput::Gamepad(GamepadButton::South, 1.),
+            ],
+        };
+        let text = format!(
+            "{}\n{}\n",
+            serde_json::to_string(&RecordingHeader { seed: 7 }).unwrap(),
+            serde_json::to_string(&frame).unwrap(),
+        );
+
+        let recording = Recording::parse(&text).unwrap();
+        assert_eq!(recording.header.seed, 7);
+        assert_eq!(recording.frames, vec![frame]);
+        assert!(Recording::parse("").is_err());
+    }
+}
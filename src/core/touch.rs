@@ -1,6 +1,4 @@
-use bevy::prelude::*;
-
-use super::mouse::MousePosition;
+use bevy::{picking::PickingSystems, prelude::*, sprite::{Anchor, SpritePickingMode, SpritePickingSettings}, transform::TransformSystems};
 
 pub struct TouchPlugin;
 
@@ -17,6 +15,21 @@ impl Default for TouchArea {
     }
 }
 
+/// Lets lower touch areas be touched through this one, e.g. while it is dragged.
+#[derive(Component)]
+pub struct TouchPassThrough;
+
+/// Invisible sprite hit by the sprite picking backend in place of its touch area.
+#[derive(Component)]
+struct TouchHitbox;
+
+/// Pointer state of a touch area, set by the picking events.
+#[derive(Component, Default)]
+pub(super) struct PointerInteraction {
+    pub over: bool,
+    pub pressed: bool,
+}
+
 #[derive(Component, PartialEq, Clone)]
 pub enum TouchState {
     None,
@@ -52,46 +65,142 @@ pub fn just_touched<T: Component>(
 impl Plugin for TouchPlugin {
     fn build(&self, app: &mut App) {
         app
+            // touch areas are plain rectangles, transparent pixels still count,
+            // only hitboxes and overlays are pickable, the camera carries `SpritePickingCamera`
+            .insert_resource(SpritePickingSettings {
+                require_markers: true,
+                picking_mode: SpritePickingMode::BoundingBox,
+            })
+            .add_observer(on_pointer_over)
+            .add_observer(on_pointer_out)
+            .add_observer(on_pointer_press)
+            .add_observer(on_pointer_release)
             .add_systems(Update, add_touch_state)
+            .add_systems(Update, update_pass_through)
+            .add_systems(PostUpdate, keep_hitbox_size.before(TransformSystems::Propagate))
             // needs to be pre update, so other states can properly read is_just_touched
-            .add_systems(PreUpdate, detect_touch);
+            .add_systems(PreUpdate, detect_touch.after(PickingSystems::Last));
     }
 }
 
 fn add_touch_state(
     mut commands: Commands,
-    entities: Query<Entity, Added<TouchArea>>,
+    entities: Query<(Entity, &TouchArea, Option<&Anchor>), Added<TouchArea>>,
 ) {
     entities 
         .into_iter()
-        .for_each(|touchable| {
+        .for_each(|(touchable, area, anchor)| {
             commands
                 .entity(touchable)
-                .insert(TouchState::None);
+                .insert((
+                    TouchState::None,
+                    PointerInteraction::default(),
+                ))
+                .with_child((
+                    TouchHitbox,
+                    Sprite::from_color(Color::NONE, area.area),
+                    anchor.copied().unwrap_or_default(),
+                    Transform::default(),
+                    Pickable::default(),
+                ));
         });
 }
 
-fn is_touching(entity_pos: &Vec2, size: &Vec2, mouse_pos: &Vec2) -> bool {
-    let half = size * 0.5;
+fn update_pass_through(
+    areas: Query<(&Children, Has<TouchPassThrough>), With<TouchArea>>,
+    mut hitboxes: Query<&mut Pickable, With<TouchHitbox>>,
+) {
+    for (children, pass_through) in &areas {
+        let pickable = Pickable {
+            should_block_lower: !pass_through,
+            is_hoverable: true,
+        };
 
-    let min = entity_pos - half;
-    let max = entity_pos + half;
+        let mut hitboxes = hitboxes.iter_many_mut(children);
+        while let Some(mut hitbox) = hitboxes.fetch_next() {
+            hitbox.set_if_neq(pickable.clone());
+        }
+    }
+}
 
-    (min.x..=max.x).contains(&mouse_pos.x) && (min.y..=max.y).contains(&mouse_pos.y)
+/// Hitboxes keep the size of the touch area while it is scaled, e.g. by `ScaleOnTouch`,
+/// so a grown tile does not steal picks from its neighbours.
+fn keep_hitbox_size(
+    areas: Query<(&Transform, &Children), (With<TouchArea>, Changed<Transform>, Without<TouchHitbox>)>,
+    mut hitboxes: Query<&mut Transform, With<TouchHitbox>>,
+) {
+    for (transform, children) in &areas {
+        if transform.scale.cmpeq(Vec3::ZERO).any() {
+            continue
+        }
+
+        let scale = transform.scale.recip();
+        let mut hitboxes = hitboxes.iter_many_mut(children);
+        while let Some(mut hitbox) = hitboxes.fetch_next() {
+            if hitbox.scale != scale {
+                hitbox.scale = scale;
+            }
+        }
+    }
 }
 
-pub fn detect_touch(
-    time: Res<Time>,
-    mouse_position: Res<MousePosition>,
-    mut entities: Query<(&GlobalTransform, &TouchArea, &mut TouchState)>,
+// Pointer events bubble up from the hitbox, the closest touch area handles them.
+
+fn on_pointer_over(
+    mut over: On<Pointer<Over>>,
+    mut areas: Query<&mut PointerInteraction>,
 ) {
-    let world_pos = mouse_position.0;
+    if let Ok(mut interaction) = areas.get_mut(over.entity) {
+        interaction.over = true;
+        over.propagate(false);
+    }
+}
+
+fn on_pointer_out(
+    mut out: On<Pointer<Out>>,
+    mut areas: Query<&mut PointerInteraction>,
+) {
+    if let Ok(mut interaction) = areas.get_mut(out.entity) {
+        interaction.over = false;
+        interaction.pressed = false;
+        out.propagate(false);
+    }
+}
 
-    for (transform, touchable, mut touch_state) in &mut entities {
-        let entity_pos = transform.translation().truncate();
-        let is_touching = is_touching(&entity_pos, &touchable.area, &world_pos);
+fn on_pointer_press(
+    mut press: On<Pointer<Press>>,
+    mut areas: Query<&mut PointerInteraction>,
+) {
+    if press.button != PointerButton::Primary {
+        return
+    }
+
+    if let Ok(mut interaction) = areas.get_mut(press.entity) {
+        interaction.pressed = true;
+        press.propagate(false);
+    }
+}
 
-        match (is_touching, touch_state.clone()) {
+fn on_pointer_release(
+    mut release: On<Pointer<Release>>,
+    mut areas: Query<&mut PointerInteraction>,
+) {
+    if release.button != PointerButton::Primary {
+        return
+    }
+
+    if let Ok(mut interaction) = areas.get_mut(release.entity) {
+        interaction.pressed = false;
+        release.propagate(false);
+    }
+}
+
+pub fn detect_touch(
+    time: Res<Time>,
+    mut entities: Query<(&PointerInteraction, &mut TouchState)>,
+) {
+    for (interaction, mut touch_state) in &mut entities {
+        match (interaction.over, touch_state.clone()) {
             (false, _) => {
                 touch_state.set_if_neq(TouchState::None);
             },
@@ -114,4 +223,3 @@ pub fn detect_touch(
     }
 
 }
-
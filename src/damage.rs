@@ -0,0 +1,225 @@
+use bevy::{platform::collections::HashMap, prelude::*};
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::{grid::GridTileColor, healthbar::Shield, status::StatusEffects};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Deserialize)]
+pub enum DamageType {
+    Nature,
+    Fire,
+    Water,
+    Earth,
+    Arcane,
+}
+
+impl DamageType {
+    pub fn from_color(color: GridTileColor) -> Self {
+        match color {
+            GridTileColor::Green => DamageType::Nature,
+            GridTileColor::Red => DamageType::Fire,
+            GridTileColor::Blue => DamageType::Water,
+            GridTileColor::Brown => DamageType::Earth,
+            GridTileColor::Multicolor => DamageType::Arcane,
+            GridTileColor::Stone => DamageType::Earth,
+        }
+    }
+}
+
+/// Damage multipliers by type, below 1.0 resists and above 1.0 is a weakness.
+#[derive(Component, Default)]
+pub struct Resistances {
+    pub multipliers: HashMap<DamageType, f32>,
+}
+
+impl Resistances {
+    pub fn multiplier(&self, damage_type: DamageType) -> f32 {
+        self.multipliers.get(&damage_type).copied().unwrap_or(1.0)
+    }
+}
+
+/// Flat amount absorbed from every hit, applied after resistances.
+#[derive(Component, Default, Deref, DerefMut)]
+pub struct Armor(pub u64);
+
+/// Chance to multiply the damage of a hit, on a card or on the player as a relic.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct CriticalHit {
+    pub chance: f32,
+    pub multiplier: f32,
+}
+
+/// Range the damage of a hit is multiplied by, on a card or on the player as a relic.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct DamageVariance {
+    pub min: f32,
+    pub max: f32,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct DamageRoll {
+    pub amount: u64,
+    pub is_crit: bool,
+}
+
+/// Rolls variance and critical hit, the rng is only used when a rule is present.
+pub fn roll_damage<R: Rng + ?Sized>(
+    amount: u64,
+    crit: Option<&CriticalHit>,
+    variance: Option<&DamageVariance>,
+    rng: &mut R,
+) -> DamageRoll {
+    let mut amount = amount as f32;
+
+    if let Some(variance) = variance {
+        let (min, max) = (variance.min.min(variance.max), variance.max.max(variance.min));
+        amount *= rng.random_range(min..=max);
+    }
+
+    let is_crit = crit
+        .map(|crit| rng.random::<f32>() < crit.chance)
+        .unwrap_or(false);
+
+    if let (true, Some(crit)) = (is_crit, crit) {
+        amount *= crit.multiplier;
+    }
+
+    DamageRoll {
+        amount: amount.round() as u64,
+        is_crit,
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Effectiveness {
+    Normal,
+    Weak,
+    Resisted,
+}
+
+impl Effectiveness {
+    pub fn label(&self) -> Option<&'static str> {
+        match self {
+            Effectiveness::Normal => None,
+            Effectiveness::Weak => Some("weak!"),
+            Effectiveness::Resisted => Some("resisted"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct DamageResult {
+    /// Damage that reaches the target's health.
+    pub amount: u64,
+    /// Damage absorbed by armor.
+    pub absorbed: u64,
+    /// Damage taken off the shield.
+    pub blocked: u64,
+    pub effectiveness: Effectiveness,
+    pub is_crit: bool,
+}
+
+/// Written with the final amount once damage has been applied to a target.
+#[derive(Message, Clone)]
+pub struct DamageDealt {
+    pub target: Entity,
+    pub damage_type: DamageType,
+    pub result: DamageResult,
+}
+
+/// Defenses of the hit entity, shared by the cast and the damage preview.
+#[derive(Clone, Copy, Default)]
+pub struct HitTarget<'a> {
+    pub resistances: Option<&'a Resistances>,
+    pub armor: Option<&'a Armor>,
+    pub effects: Option<&'a StatusEffects>,
+    pub shield: Option<&'a Shield>,
+}
+
+/// Status effects of both sides first, then resistances and armor, the shield takes the rest first.
+pub fn calculate_hit(
+    amount: u64,
+    damage_type: DamageType,
+    attacker: Option<&StatusEffects>,
+    target: HitTarget,
+) -> DamageResult {
+    let amount = attacker.map_or(amount, |effects| effects.damage_dealt(amount));
+    let amount = target.effects.map_or(amount, |effects| effects.damage_taken(amount));
+    let mut result = calculate_damage(amount, damage_type, target.resistances, target.armor);
+    result.blocked = target.shield.map_or(0, |shield| shield.0).min(result.amount);
+    result.amount -= result.blocked;
+    result
+}
+
+pub fn calculate_damage(
+    amount: u64,
+    damage_type: DamageType,
+    resistances: Option<&Resistances>,
+    armor: Option<&Armor>,
+) -> DamageResult {
+    let multiplier = resistances
+        .map(|resistances| resistances.multiplier(damage_type))
+        .unwrap_or(1.0);
+
+    let effectiveness = if multiplier > 1.0 {
+        Effectiveness::Weak
+    } else if multiplier < 1.0 {
+        Effectiveness::Resisted
+    } else {
+        Effectiveness::Normal
+    };
+
+    let modified = (amount as f32 * multiplier).round() as u64;
+    let absorbed = armor.map(|armor| armor.0).unwrap_or(0).min(modified);
+
+    DamageResult {
+        amount: modified - absorbed,
+        absorbed,
+        blocked: 0,
+        effectiveness,
+        is_crit: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calculate_damage() {
+        let mut resistances = Resistances::default();
+        resistances.multipliers.insert(DamageType::Water, 2.0);
+        resistances.multipliers.insert(DamageType::Nature, 0.5);
+
+        let result = calculate_damage(4, DamageType::Water, Some(&resistances), Some(&Armor(1)));
+        assert_eq!(7, result.amount);
+        assert_eq!(1, result.absorbed);
+        assert_eq!(Effectiveness::Weak, result.effectiveness);
+
+        let result = calculate_damage(4, DamageType::Nature, Some(&resistances), None);
+        assert_eq!(2, result.amount);
+        assert_eq!(Effectiveness::Resisted, result.effectiveness);
+
+        let result = calculate_damage(1, DamageType::Fire, None, Some(&Armor(3)));
+        assert_eq!(0, result.amount);
+        assert_eq!(1, result.absorbed);
+    }
+
+    #[test]
+    fn test_roll_damage() {
+        use rand::{SeedableRng, rngs::StdRng};
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let always = CriticalHit { chance: 1.0, multiplier: 2.0 };
+        let never = CriticalHit { chance: 0.0, multiplier: 2.0 };
+
+        assert_eq!(DamageRoll { amount: 10, is_crit: true }, roll_damage(5, Some(&always), None, &mut rng));
+        assert_eq!(DamageRoll { amount: 5, is_crit: false }, roll_damage(5, Some(&never), None, &mut rng));
+
+        let variance = DamageVariance { min: 0.5, max: 1.5 };
+        for _ in 0..100 {
+            let roll = roll_damage(10, None, Some(&variance), &mut rng);
+            assert!((5..=15).contains(&roll.amount));
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use bevy::prelude::*;
+
+use bevy_rand::{global::GlobalRng, prelude::WyRand};
+use rand::prelude::IndexedRandom;
+
+use crate::enemy::{Enemy, EnemyDefeated};
+use crate::game::TurnState;
+use crate::healthbar::{Health, MaxHealth, Shield};
+use crate::layout::{ContentView, DisplayGameView, DisplayMainMenu, GameView};
+use crate::player::Player;
+use crate::score::Score;
+use crate::simple_button::{button_system, SimpleButton};
+use crate::status::StatusEffects;
+
+/// Archetype ids of the enemies of every encounter, one is picked at random.
+const ENCOUNTERS: &[&[&str]] = &[
+    &["shade"],
+    &["brute"],
+    &["shade", "shade"],
+    &["warden", "shade"],
+    &["shade", "brute", "shade"],
+    &["lich"],
+];
+
+/// Horizontal distance between the enemies of an encounter.
+const ENEMY_SPACING: f32 = 200.;
+
+/// Health restored to the player after every victory.
+const VICTORY_HEAL: u64 = 10;
+
+/// Rewards collected from the enemies defeated in the current encounter.
+#[derive(Component, Default)]
+pub struct EncounterRewards {
+    pub score: u64,
+}
+
+/// Overlay shown on victory or defeat, despawned together with the game view.
+#[derive(Component)]
+pub struct EncounterResultView;
+
+/// Parent of the encounter's enemies in the top bar.
+#[derive(Component)]
+pub struct EnemiesView;
+
+/// Number of enemies the encounter spawned, dead and despawned ones included.
+#[derive(Component)]
+pub struct EncounterEnemies(pub usize);
+
+#[derive(Component)]
+pub struct NextEncounterButton;
+
+#[derive(Component)]
+pub struct MenuButton;
+
+pub struct EncounterPlugin;
+
+impl Plugin for EncounterPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Update, button_system::<NextEncounterButton, DisplayGameView>)
+            .add_systems(Update, button_system::<MenuButton, DisplayMainMenu>)
+            .add_systems(Update, spawn_encounter)
+            .add_systems(Update, collect_rewards.run_if(on_message::<EnemyDefeated>))
+            .add_systems(OnEnter(TurnState::Victory), display_victory)
+            .add_systems(OnEnter(TurnState::Defeat), display_defeat)
+            .add_systems(OnExit(TurnState::Defeat), start_new_run);
+    }
+}
+
+fn spawn_encounter(
+    mut commands: Commands,
+    views: Query<Entity, Added<EnemiesView>>,
+    mut rng: Single<&mut WyRand, With<GlobalRng>>,
+) {
+    for view in &views {
+        let Some(ids) = ENCOUNTERS.choose(&mut rng) else {
+            continue
+        };
+
+        let center = (ids.len() - 1) as f32 / 2.;
+        commands
+            .entity(view)
+            .insert(EncounterEnemies(ids.len()))
+            .with_children(|view| {
+                for (i, id) in ids.iter().enumerate() {
+                    view.spawn((
+                        Enemy::create(id),
+                        Transform::from_xyz((i as f32 - center) * ENEMY_SPACING, 0., 0.),
+                    ));
+                }
+            });
+    }
+}
+
+fn collect_rewards(
+    mut rewards: Single<&mut EncounterRewards, With<GameView>>,
+    mut reader: MessageReader<EnemyDefeated>,
+) {
+    for defeated in reader.read() {
+        rewards.score += defeated.reward;
+    }
+}
+
+/// Status effects end with the encounter.
+fn display_victory(
+    mut commands: Commands,
+    content_view: Single<Entity, With<ContentView>>,
+    rewards: Single<&EncounterRewards, With<GameView>>,
+    mut player: Single<(&mut Health, &MaxHealth, &mut StatusEffects), With<Player>>,
+    mut score: Single<&mut Score>,
+) {
+    let (ref mut health, max_health, ref mut effects) = *player;
+    effects.stacks.clear();
+    let heal = VICTORY_HEAL.min(max_health.0 - health.0.min(max_health.0));
+    health.0 += heal;
+    score.0 += rewards.score;
+
+    let text = format!("victory!\n+{} score\n+{} hp", rewards.score, heal);
+    spawn_result_view(&mut commands, *content_view, text, true);
+}
+
+fn display_defeat(
+    mut commands: Commands,
+    content_view: Single<Entity, With<ContentView>>,
+    score: Single<&Score>,
+) {
+    let text = format!("defeat\nfinal score {}", score.0);
+    spawn_result_view(&mut commands, *content_view, text, false);
+}
+
+/// Leaving the defeat screen starts over with a fresh player and score.
+fn start_new_run(
+    mut player: Single<(&mut Health, &MaxHealth, &mut Shield, &mut StatusEffects), With<Player>>,
+    mut score: Single<&mut Score>,
+) {
+    let (ref mut health, max_health, ref mut shield, ref mut effects) = *player;
+    health.0 = max_health.0;
+    shield.0 = 0;
+    effects.stacks.clear();
+    score.0 = 0;
+}
+
+fn spawn_result_view(
+    commands: &mut Commands,
+    content_view: Entity,
+    text: String,
+    can_continue: bool,
+) {
+    commands
+        .entity(content_view)
+        .with_children(|view| {
+            view.spawn((
+                EncounterResultView,
+                Pickable::default(),
+                Name::new("Encounter Result"),
+                Sprite::from_color(Color::linear_rgba(0., 0., 0., 0.85), Vec2::new(800., 600.)),
+                Transform::from_xyz(0., 0., 10.),
+            )).with_children(|overlay| {
+                overlay.spawn((
+                    Text2d::new(text),
+                    Transform::from_xyz(0., 64., 1.),
+                ));
+
+                if can_continue {
+                    overlay.spawn(SimpleButton::create(NextEncounterButton, "next", (-64., -64.).into()));
+                    overlay.spawn(SimpleButton::create(MenuButton, "menu", (64., -64.).into()));
+                } else {
+                    overlay.spawn(SimpleButton::create(MenuButton, "menu", (0., -64.).into()));
+                }
+            });
+        });
+}
@@ -0,0 +1,148 @@
+use bevy::{asset::{io::Reader, AssetLoader, LoadContext}, platform::collections::HashMap, prelude::*};
+use serde::Deserialize;
+
+use crate::{damage::DamageType, grid::GridTileColor, status::StatusEffect};
+
+/// File with all enemy archetypes, loaded on startup.
+const ARCHETYPES_PATH: &str = "enemies/archetypes.enemies.ron";
+
+/// Enemy archetypes by id.
+#[derive(Asset, TypePath, Deserialize, Deref)]
+#[serde(transparent)]
+pub struct EnemyArchetypes(pub HashMap<String, EnemyArchetype>);
+
+#[derive(Deserialize, Clone)]
+pub struct EnemyArchetype {
+    pub name: String,
+    pub sprite: EnemySprite,
+    pub health: u64,
+    #[serde(default)]
+    pub resistances: HashMap<DamageType, f32>,
+    #[serde(default)]
+    pub armor: u64,
+    /// Score granted for defeating the enemy.
+    #[serde(default)]
+    pub reward: u64,
+    pub moves: Vec<EnemyMove>,
+    /// Boss phases, ordered from the highest threshold to the lowest.
+    #[serde(default)]
+    pub phases: Vec<EnemyPhase>,
+}
+
+/// Behavior of a boss once its health drops to `threshold` of its maximum.
+#[derive(Deserialize, Clone)]
+pub struct EnemyPhase {
+    pub threshold: f32,
+    /// Replaces the sprite of the previous phase.
+    #[serde(default)]
+    pub sprite: Option<EnemySprite>,
+    /// Replaces the moves of the previous phase.
+    pub moves: Vec<EnemyMove>,
+    /// Resolved right away when the phase starts.
+    #[serde(default)]
+    pub on_enter: Vec<EnemyMove>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct EnemySprite {
+    pub image: String,
+    pub size: f32,
+    /// Color multiplied with the image, ignored by animated sprites.
+    #[serde(default)]
+    pub tint: Option<(f32, f32, f32)>,
+    /// Horizontal animation sheet, the image is used as is without it.
+    #[serde(default)]
+    pub animation: Option<EnemyAnimation>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct EnemyAnimation {
+    pub tile_size: (u32, u32),
+    pub frames: u32,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub enum EnemyMove {
+    /// Damage the player.
+    Attack(u64),
+    /// Restore the enemy's own health.
+    Heal(u64),
+    /// Gain shield until the start of the next enemy turn.
+    Block(u64),
+    /// Increase the damage of every following attack.
+    Buff(u64),
+    /// Lock random tiles of the next board.
+    LockTiles(usize),
+    /// Recolor a random row of the next board.
+    RecolorRow(GridTileColor),
+    /// Turn random tiles of the next board into stones.
+    Stones(usize),
+    /// Take moves away from the next board.
+    StealMoves(usize),
+    /// Move the requirement cells of a card of the next hand.
+    ScrambleCard,
+    /// Apply stacks of a status effect to the player.
+    Inflict(StatusEffect, u64),
+}
+
+#[derive(Resource)]
+pub struct EnemyArchetypesHandle(pub Handle<EnemyArchetypes>);
+
+#[derive(Default, TypePath)]
+struct EnemyArchetypesLoader;
+
+impl AssetLoader for EnemyArchetypesLoader {
+    type Asset = EnemyArchetypes;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["enemies.ron"]
+    }
+}
+
+pub struct EnemyArchetypesPlugin;
+
+impl Plugin for EnemyArchetypesPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_asset::<EnemyArchetypes>()
+            .init_asset_loader::<EnemyArchetypesLoader>()
+            .add_systems(Startup, load_archetypes);
+    }
+}
+
+fn load_archetypes(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    commands.insert_resource(EnemyArchetypesHandle(asset_server.load(ARCHETYPES_PATH)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archetypes_file() {
+        let archetypes: EnemyArchetypes = ron::de::from_str(include_str!("../../assets/enemies/archetypes.enemies.ron"))
+            .expect("archetypes file must be valid");
+
+        assert!(archetypes.contains_key("shade"));
+        assert!(archetypes.values().all(|archetype| !archetype.moves.is_empty()));
+        assert!(archetypes.values().all(|archetype| {
+            archetype.phases.windows(2).all(|phases| phases[0].threshold > phases[1].threshold)
+        }));
+    }
+}
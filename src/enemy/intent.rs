@@ -0,0 +1,150 @@
+use bevy::prelude::*;
+
+use crate::core::prelude::*;
+use crate::game::TurnStarted;
+use crate::grid::GridTileColor;
+use crate::tooltip_on_touch::TooltipOnTouch;
+
+use super::{Enemy, EnemyMove, EnemyMoves, EnemyStrength};
+
+/// Move the enemy uses in the next enemy turn, taken when it resolves.
+#[derive(Component)]
+pub struct EnemyIntent(pub Option<EnemyMove>);
+
+#[derive(Component, Deref, DerefMut)]
+pub struct EnemyIntentYOffset(pub f32);
+
+#[derive(Component)]
+struct EnemyIntentIcon;
+
+#[derive(Component)]
+struct EnemyIntentText;
+
+/// Ids of child components of the intent.
+#[derive(Component)]
+struct EnemyIntentComponents {
+    icon: Entity,
+    text: Entity,
+}
+
+impl EnemyMove {
+    fn icon_color(&self) -> Color {
+        match self {
+            EnemyMove::Attack(_) => Color::linear_rgb(0.9, 0.1, 0.1),
+            EnemyMove::Heal(_) => Color::linear_rgb(0.1, 0.8, 0.2),
+            EnemyMove::Block(_) => Color::linear_rgb(0.4, 0.7, 1.),
+            EnemyMove::Buff(_) => Color::linear_rgb(0.9, 0.7, 0.1),
+            EnemyMove::LockTiles(_) => Color::linear_rgb(0.35, 0.35, 0.5),
+            EnemyMove::RecolorRow(color) => color.color(),
+            EnemyMove::Stones(_) => GridTileColor::Stone.color(),
+            EnemyMove::StealMoves(_) => Color::linear_rgb(0.6, 0.2, 0.8),
+            EnemyMove::ScrambleCard => Color::linear_rgb(1., 0.6, 1.),
+            EnemyMove::Inflict(effect, _) => effect.color(),
+        }
+    }
+
+    /// Number shown next to the icon, attacks include the enemy's strength.
+    fn amount(&self, strength: u64) -> Option<u64> {
+        match self {
+            EnemyMove::Attack(amount) => Some(amount + strength),
+            EnemyMove::Heal(amount) | EnemyMove::Block(amount) | EnemyMove::Buff(amount) | EnemyMove::Inflict(_, amount) => Some(*amount),
+            EnemyMove::LockTiles(count) | EnemyMove::Stones(count) | EnemyMove::StealMoves(count) => Some(*count as u64),
+            EnemyMove::RecolorRow(_) | EnemyMove::ScrambleCard => None,
+        }
+    }
+
+    fn description(&self, strength: u64) -> String {
+        let amount = self.amount(strength).unwrap_or(1);
+        match self {
+            EnemyMove::Attack(_) => format!("Attacks for {} damage.", amount),
+            EnemyMove::Heal(_) => format!("Heals itself for {}.", amount),
+            EnemyMove::Block(_) => format!("Blocks the next {} damage.", amount),
+            EnemyMove::Buff(_) => format!("Increases its attacks by {}.", amount),
+            EnemyMove::LockTiles(_) => format!("Locks {} tiles of the next board.", amount),
+            EnemyMove::RecolorRow(color) => format!("Turns a row of the next board {:?}.", color),
+            EnemyMove::Stones(_) => format!("Turns {} tiles of the next board into stones.", amount),
+            EnemyMove::StealMoves(_) => format!("Steals {} moves from the next board.", amount),
+            EnemyMove::ScrambleCard => "Moves the requirement cells of a card in the next hand.".to_string(),
+            EnemyMove::Inflict(effect, _) => format!("Applies {} {:?} to you.", amount, effect),
+        }
+    }
+}
+
+pub struct EnemyIntentPlugin;
+
+impl Plugin for EnemyIntentPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Update, choose_intents.run_if(on_message::<TurnStarted>))
+            .add_systems(Update, draw_intent)
+            .add_systems(Update, update_intent.after(choose_intents));
+    }
+}
+
+fn choose_intents(
+    enemies: Query<(&mut EnemyIntent, &mut EnemyMoves), With<Enemy>>,
+) {
+    for (mut intent, mut moves) in enemies {
+        intent.0 = moves.advance();
+    }
+}
+
+fn draw_intent(
+    mut commands: Commands,
+    query: Query<(Entity, &EnemyIntentYOffset), Added<EnemyIntent>>,
+) {
+    for (entity, y_offset) in query {
+        let icon = commands.spawn((
+            EnemyIntentIcon,
+            Sprite::from_color(Color::NONE, Vec2::splat(16.)),
+            Transform::from_xyz(-12., y_offset.0, 1.),
+            TouchArea {
+                area: Vec2::splat(24.),
+            },
+            TooltipOnTouch(String::new()),
+        )).id();
+
+        let text = commands.spawn((
+            EnemyIntentText,
+            Text2d::new(""),
+            Transform::from_xyz(12., y_offset.0, 1.),
+        )).id();
+
+        commands
+            .entity(entity)
+            .add_children(&[icon, text])
+            .try_insert(EnemyIntentComponents {
+                icon,
+                text,
+            });
+    }
+}
+
+fn update_intent(
+    query: Query<(&EnemyIntent, &EnemyStrength, &EnemyIntentComponents), Or<(Changed<EnemyIntent>, Changed<EnemyStrength>, Added<EnemyIntentComponents>)>>,
+    mut icons: Query<(&mut Sprite, &mut TooltipOnTouch), With<EnemyIntentIcon>>,
+    mut texts: Query<&mut Text2d, With<EnemyIntentText>>,
+) {
+    for (intent, strength, components) in query {
+        if let Ok((mut sprite, mut tooltip)) = icons.get_mut(components.icon) {
+            match &intent.0 {
+                Some(enemy_move) => {
+                    sprite.color = enemy_move.icon_color();
+                    tooltip.0 = enemy_move.description(strength.0);
+                },
+                None => {
+                    sprite.color = Color::NONE;
+                    tooltip.0.clear();
+                },
+            }
+        }
+
+        if let Ok(mut text) = texts.get_mut(components.text) {
+            text.0 = intent.0
+                .as_ref()
+                .and_then(|enemy_move| enemy_move.amount(strength.0))
+                .map(|amount| amount.to_string())
+                .unwrap_or_default();
+        }
+    }
+}
@@ -0,0 +1,345 @@
+mod archetype;
+mod intent;
+mod phase;
+mod targeting;
+
+use bevy::{ecs::system::EntityCommands, prelude::*};
+use crate::{animated_sprite::AnimatedSprite, card::{tasks::{Task, Tasks}, CardsView, ScrambledCards}, grid::{BoardEffect, Grid, GridData}, combat_log::CombatLogEntry, core::prelude::*, damage::{Armor, Resistances}, game::{GameSpeed, TurnState}, healthbar::{Health, Healthbar, HealthbarSegments, HealthbarStyle, HealthbarTextFormat, HealthbarYOffset, MaxHealth, Shield}, layout::GameView, player::{DamagePlayer, Player}, status::{StatusEffect, StatusEffects, StatusIcons}, tooltip_on_touch::TooltipOnTouch};
+
+/// Delay before every enemy action of the enemy turn.
+const ENEMY_ACTION_DELAY_SECS: f32 = 0.75;
+
+pub use archetype::{EnemyArchetypes, EnemyMove};
+use archetype::{EnemyArchetypesHandle, EnemyArchetypesPlugin, EnemySprite};
+use intent::{EnemyIntent, EnemyIntentPlugin, EnemyIntentYOffset};
+pub use targeting::CurrentTarget;
+use targeting::EnemyTargetingPlugin;
+use phase::{EnemyPhasePlugin, EnemyPhases};
+
+#[derive(Component)]
+pub struct Enemy;
+
+impl Enemy {
+    /// Enemy set up from the archetype with the given id once the archetypes are loaded.
+    pub fn create(id: &str) -> impl Bundle {
+        (
+            Enemy,
+            EnemyArchetypeId(id.to_string()),
+        )
+    }
+}
+
+/// Id of the archetype in `enemies/archetypes.enemies.ron`.
+#[derive(Component, Clone)]
+pub struct EnemyArchetypeId(pub String);
+
+/// Delay between the killing blow and the enemy being despawned.
+const DEATH_SECS: f32 = 0.75;
+
+/// Score granted for defeating the enemy.
+#[derive(Component, Deref, DerefMut)]
+pub struct EnemyReward(pub u64);
+
+/// Fades the enemy out before it is despawned.
+#[derive(Component)]
+struct EnemyDying(Timer);
+
+/// Written once when the enemy's health reaches zero.
+#[derive(Message)]
+pub struct EnemyDefeated {
+    pub reward: u64,
+}
+
+/// Extra damage added to every attack of the enemy.
+#[derive(Component, Default, Deref, DerefMut)]
+pub struct EnemyStrength(pub u64);
+
+/// Written by the enemy turn's tasks, one for every intent.
+#[derive(Message)]
+pub struct ResolveEnemyMove {
+    pub enemy: Entity,
+    pub enemy_move: EnemyMove,
+}
+
+/// Moves of the enemy, one is used every enemy turn in a loop.
+#[derive(Component)]
+pub struct EnemyMoves {
+    pub moves: Vec<EnemyMove>,
+    next: usize,
+}
+
+impl EnemyMoves {
+    /// Move used this turn, the next call returns the following one.
+    pub fn advance(&mut self) -> Option<EnemyMove> {
+        if self.moves.is_empty() {
+            return None
+        }
+
+        let enemy_move = self.moves[self.next % self.moves.len()].clone();
+        self.next = (self.next + 1) % self.moves.len();
+        Some(enemy_move)
+    }
+}
+
+pub struct EnemyPlugin;
+
+impl Plugin for EnemyPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_plugins(EnemyArchetypesPlugin)
+            .add_plugins(EnemyIntentPlugin)
+            .add_plugins(EnemyTargetingPlugin)
+            .add_plugins(EnemyPhasePlugin)
+            .add_message::<EnemyDefeated>()
+            .add_message::<ResolveEnemyMove>()
+            .add_systems(Update, resolve_enemy_moves.run_if(on_message::<ResolveEnemyMove>))
+            .add_systems(Update, setup_enemy)
+            .add_systems(Update, kill_enemy)
+            .add_systems(Update, animate_enemy_death)
+            .add_systems(OnEnter(TurnState::EnemyTurn), enemy_turn);
+    }
+}
+
+fn setup_enemy(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    handle: Res<EnemyArchetypesHandle>,
+    archetypes: Res<Assets<EnemyArchetypes>>,
+    enemies: Query<(Entity, &EnemyArchetypeId), (With<Enemy>, Without<Health>)>,
+) {
+    // enemies wait until the archetypes are loaded
+    let Some(archetypes) = archetypes.get(&handle.0) else {
+        return
+    };
+
+    enemies
+        .into_iter()
+        .for_each(|(enemy, id)| {
+            let Some(archetype) = archetypes.get(&id.0) else {
+                error!("unknown enemy archetype: {}", id.0);
+                commands.entity(enemy).despawn();
+                return
+            };
+
+            let size = Vec2::splat(archetype.sprite.size);
+            let mut moves = EnemyMoves {
+                moves: archetype.moves.clone(),
+                next: 0,
+            };
+            let intent = EnemyIntent(moves.advance());
+            let mut entity_commands = commands.entity(enemy);
+            insert_sprite(&mut entity_commands, &asset_server, &archetype.sprite);
+
+            if !archetype.phases.is_empty() {
+                entity_commands.try_insert((
+                    HealthbarSegments(archetype.phases.iter().map(|phase| phase.threshold).collect()),
+                    HealthbarStyle {
+                        size: Vec2::new(240., 24.),
+                        health_color: Color::linear_rgb(0.7, 0., 0.9),
+                        text_format: HealthbarTextFormat::Percent,
+                        ..default()
+                    },
+                    EnemyPhases::new(archetype.phases.clone()),
+                ));
+            }
+
+            entity_commands
+                .try_insert((
+                    Name::new(archetype.name.clone()),
+                    (
+                        Health(archetype.health),
+                        MaxHealth(archetype.health),
+                        Shield(0),
+                    ),
+                    (
+                        TouchArea {
+                            area: size,
+                        },
+                        PressArea,
+                        GestureArea,
+                    ),
+                    TooltipOnTouch(archetype.name.clone()),
+                    Healthbar,
+                    HealthbarYOffset(-size.y / 2.),
+                    StatusEffects::default(),
+                    Resistances {
+                        multipliers: archetype.resistances.clone(),
+                    },
+                    Armor(archetype.armor),
+                    moves,
+                    intent,
+                    EnemyIntentYOffset(size.y / 2. + 16.),
+                    EnemyStrength(0),
+                    EnemyReward(archetype.reward),
+                ))
+                .with_child((
+                    StatusIcons {
+                        owner: enemy,
+                    },
+                    Transform::from_xyz(0., -size.y / 2. - 24., 1.),
+                    Visibility::Inherited,
+                ));
+        });
+}
+
+/// Animated sprites are removed first, so a new one is set up again.
+fn insert_sprite(
+    entity_commands: &mut EntityCommands,
+    asset_server: &AssetServer,
+    enemy_sprite: &EnemySprite,
+) {
+    let size = Vec2::splat(enemy_sprite.size);
+    entity_commands.try_remove::<AnimatedSprite>();
+    match &enemy_sprite.animation {
+        Some(animation) => {
+            entity_commands.try_insert(AnimatedSprite {
+                filename: enemy_sprite.image.clone(),
+                tilesize: UVec2::from(animation.tile_size),
+                frames: animation.frames,
+                custom_size: Some(size),
+                start_frame: None,
+            });
+        },
+        None => {
+            let mut sprite = Sprite::from_image(asset_server.load(&enemy_sprite.image));
+            sprite.custom_size = Some(size);
+            if let Some((r, g, b)) = enemy_sprite.tint {
+                sprite.color = Color::linear_rgb(r, g, b);
+            }
+            entity_commands.try_insert(sprite);
+        },
+    }
+}
+
+/// Queues the intent of every living enemy, the next intents are chosen at the start of the player turn.
+/// Stunned enemies lose their intent, blocks last until the enemy's next turn.
+pub fn enemy_turn(
+    mut tasks: Single<&mut Tasks, With<GameView>>,
+    enemies: Query<(Entity, &Name, &Health, &mut Shield, &StatusEffects, &mut EnemyIntent), With<Enemy>>,
+    mut log: MessageWriter<CombatLogEntry>,
+) {
+    for (order, (enemy, name, health, mut shield, effects, mut intent)) in enemies.into_iter().enumerate() {
+        shield.0 = 0;
+        if health.0 == 0 {
+            continue
+        }
+
+        let Some(enemy_move) = intent.0.take() else {
+            continue
+        };
+
+        if effects.has(StatusEffect::Stun) {
+            log.write(CombatLogEntry::from_enemy(name, "stunned", 0));
+            continue
+        }
+
+        tasks.insert(ENEMY_ACTION_DELAY_SECS, Task::write_message(order as u64, ResolveEnemyMove {
+            enemy,
+            enemy_move,
+        }));
+    }
+}
+
+/// Board abilities take effect on the next board and hand.
+fn resolve_enemy_moves(
+    mut enemies: Query<(&Name, &mut Health, &MaxHealth, &mut Shield, &mut EnemyStrength, &StatusEffects), With<Enemy>>,
+    mut player_effects: Single<&mut StatusEffects, (With<Player>, Without<Enemy>)>,
+    mut grid: Single<&mut GridData, With<Grid>>,
+    mut scrambled: Single<&mut ScrambledCards, With<CardsView>>,
+    mut reader: MessageReader<ResolveEnemyMove>,
+    mut damage_writer: MessageWriter<DamagePlayer>,
+    mut log: MessageWriter<CombatLogEntry>,
+) {
+    for resolve in reader.read() {
+        let Ok((name, mut health, max_health, mut shield, mut strength, effects)) = enemies.get_mut(resolve.enemy) else {
+            continue
+        };
+
+        if health.0 == 0 {
+            continue
+        }
+
+        match resolve.enemy_move {
+            EnemyMove::Attack(amount) => {
+                damage_writer.write(DamagePlayer {
+                    amount: effects.damage_dealt(amount + strength.0),
+                    source: name.to_string(),
+                });
+            },
+            EnemyMove::Heal(amount) => {
+                health.0 = (health.0 + amount).min(max_health.0);
+                log.write(CombatLogEntry::from_enemy(name, "heal", amount).with_health(health.0));
+            },
+            EnemyMove::Block(amount) => {
+                shield.0 += amount;
+                log.write(CombatLogEntry::from_enemy(name, "block", amount));
+            },
+            EnemyMove::Buff(amount) => {
+                strength.0 += amount;
+                log.write(CombatLogEntry::from_enemy(name, "buff", amount));
+            },
+            EnemyMove::LockTiles(count) => {
+                grid.add_effect(BoardEffect::Lock(count));
+                log.write(CombatLogEntry::from_enemy(name, "lock tiles", count as u64));
+            },
+            EnemyMove::RecolorRow(color) => {
+                grid.add_effect(BoardEffect::RecolorRow(color));
+                log.write(CombatLogEntry::from_enemy(name, "recolor row", 1).with_modifier(format!("{:?}", color)));
+            },
+            EnemyMove::Stones(count) => {
+                grid.add_effect(BoardEffect::Stone(count));
+                log.write(CombatLogEntry::from_enemy(name, "stones", count as u64));
+            },
+            EnemyMove::StealMoves(count) => {
+                grid.steal_moves(count);
+                log.write(CombatLogEntry::from_enemy(name, "steal moves", count as u64));
+            },
+            EnemyMove::ScrambleCard => {
+                scrambled.0 += 1;
+                log.write(CombatLogEntry::from_enemy(name, "scramble card", 1));
+            },
+            EnemyMove::Inflict(effect, stacks) => {
+                player_effects.add(effect, stacks);
+                log.write(CombatLogEntry::from_enemy(name, "inflict", stacks).with_modifier(format!("{:?}", effect)));
+            },
+        }
+    }
+}
+
+fn kill_enemy(
+    mut commands: Commands,
+    enemies: Query<(Entity, &Health, &EnemyReward), (With<Enemy>, Changed<Health>, Without<EnemyDying>)>,
+    mut writer: MessageWriter<EnemyDefeated>,
+) {
+    for (enemy, health, reward) in &enemies {
+        if health.0 > 0 {
+            continue
+        }
+
+        commands
+            .entity(enemy)
+            .try_insert(EnemyDying(Timer::from_seconds(DEATH_SECS, TimerMode::Once)));
+
+        writer.write(EnemyDefeated {
+            reward: reward.0,
+        });
+    }
+}
+
+fn animate_enemy_death(
+    mut commands: Commands,
+    time: Res<Time>,
+    speed: Res<GameSpeed>,
+    enemies: Query<(Entity, &mut EnemyDying, &mut Sprite, &mut Transform)>,
+) {
+    for (enemy, mut dying, mut sprite, mut transform) in enemies {
+        dying.0.tick(time.delta().mul_f32(speed.0));
+        if dying.0.is_finished() {
+            commands.entity(enemy).despawn();
+        } else {
+            let remaining = dying.0.fraction_remaining();
+            sprite.color.set_alpha(remaining);
+            transform.scale = Vec3::splat(0.5 + remaining * 0.5);
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use bevy::prelude::*;
+
+use crate::{combat_log::CombatLogEntry, healthbar::{Health, MaxHealth}, notification::{DisplayToast, NotificationKind}};
+
+use super::{archetype::EnemyPhase, insert_sprite, intent::EnemyIntent, Enemy, EnemyMoves, ResolveEnemyMove};
+
+/// Remaining phases of a boss, entered as its health drops.
+#[derive(Component)]
+pub struct EnemyPhases {
+    phases: Vec<EnemyPhase>,
+    /// Number of phases entered so far.
+    entered: usize,
+}
+
+impl EnemyPhases {
+    pub fn new(phases: Vec<EnemyPhase>) -> Self {
+        EnemyPhases {
+            phases,
+            entered: 0,
+        }
+    }
+
+    /// Next phase to enter at the given fraction of the maximum health.
+    fn advance(&mut self, health_fraction: f32) -> Option<&EnemyPhase> {
+        let phase = self.phases.get(self.entered)?;
+        if health_fraction > phase.threshold {
+            return None
+        }
+
+        self.entered += 1;
+        self.phases.get(self.entered - 1)
+    }
+}
+
+pub struct EnemyPhasePlugin;
+
+impl Plugin for EnemyPhasePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Update, advance_phase);
+    }
+}
+
+/// A big hit can skip phases, all of their transitions still resolve.
+fn advance_phase(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    enemies: Query<(Entity, &Name, &Health, &MaxHealth, &mut EnemyPhases, &mut EnemyMoves, &mut EnemyIntent), (With<Enemy>, Changed<Health>)>,
+    mut writer: MessageWriter<ResolveEnemyMove>,
+    mut log: MessageWriter<CombatLogEntry>,
+    mut toasts: MessageWriter<DisplayToast>,
+) {
+    for (enemy, name, health, max_health, mut phases, mut moves, mut intent) in enemies {
+        if health.0 == 0 {
+            continue
+        }
+
+        let fraction = health.0 as f32 / max_health.0 as f32;
+        while let Some(phase) = phases.advance(fraction) {
+            if let Some(sprite) = &phase.sprite {
+                insert_sprite(&mut commands.entity(enemy), &asset_server, sprite);
+            }
+
+            *moves = EnemyMoves {
+                moves: phase.moves.clone(),
+                next: 0,
+            };
+            intent.0 = moves.advance();
+
+            for enemy_move in &phase.on_enter {
+                writer.write(ResolveEnemyMove {
+                    enemy,
+                    enemy_move: enemy_move.clone(),
+                });
+            }
+
+            log.write(CombatLogEntry::from_enemy(name, "new phase", phases.entered as u64 + 1));
+            toasts.write(DisplayToast {
+                text: format!("{} enters phase {}", name, phases.entered + 1),
+                kind: NotificationKind::Warning,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_advance_phases() {
+        let phase = |threshold| EnemyPhase {
+            threshold,
+            sprite: None,
+            moves: vec![],
+            on_enter: vec![],
+        };
+        let mut phases = EnemyPhases::new(vec![phase(0.66), phase(0.33)]);
+
+        assert!(phases.advance(0.8).is_none());
+        assert_eq!(Some(0.66), phases.advance(0.5).map(|phase| phase.threshold));
+        assert!(phases.advance(0.5).is_none());
+
+        // both thresholds crossed by a single hit
+        let mut phases = EnemyPhases::new(vec![phase(0.66), phase(0.33)]);
+        assert!(phases.advance(0.2).is_some());
+        assert!(phases.advance(0.2).is_some());
+        assert!(phases.advance(0.2).is_none());
+    }
+}
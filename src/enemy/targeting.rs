@@ -0,0 +1,108 @@
+use bevy::prelude::*;
+
+use crate::{core::prelude::*, game::TurnState, healthbar::Health};
+
+use super::{Enemy, EnemyIntentYOffset};
+
+/// Enemy hit by cards with single targeting, there is at most one.
+#[derive(Component)]
+pub struct CurrentTarget;
+
+/// Marker drawn above the current target.
+#[derive(Component)]
+struct TargetIndicator;
+
+pub struct EnemyTargetingPlugin;
+
+impl Plugin for EnemyTargetingPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Update, select_target
+                .run_if(on_message::<Tap>)
+                .run_if(in_state(TurnState::PlayerTurn))
+            )
+            .add_systems(Update, retarget.after(select_target))
+            .add_systems(Update, add_indicator)
+            .add_systems(Update, display_indicator.after(retarget));
+    }
+}
+
+fn select_target(
+    mut commands: Commands,
+    enemies: Query<(Entity, &Health, Has<CurrentTarget>), With<Enemy>>,
+    mut reader: MessageReader<Tap>,
+) {
+    let Some(selected) = reader
+        .read()
+        .filter_map(|tap| enemies.get(tap.entity).ok())
+        .filter(|(_, health, _)| health.0 > 0)
+        .map(|(enemy, ..)| enemy)
+        .last() else {
+        return
+    };
+
+    for (enemy, .., is_current) in &enemies {
+        if is_current && enemy != selected {
+            commands.entity(enemy).try_remove::<CurrentTarget>();
+        }
+    }
+
+    commands.entity(selected).try_insert(CurrentTarget);
+}
+
+/// Moves the target to the leftmost living enemy when there is none or it died.
+fn retarget(
+    mut commands: Commands,
+    enemies: Query<(Entity, &Health, &Transform, Has<CurrentTarget>), With<Enemy>>,
+) {
+    let has_target = enemies
+        .iter()
+        .any(|(_, health, _, is_current)| is_current && health.0 > 0);
+    if has_target {
+        return
+    }
+
+    for (enemy, .., is_current) in &enemies {
+        if is_current {
+            commands.entity(enemy).try_remove::<CurrentTarget>();
+        }
+    }
+
+    let leftmost = enemies
+        .iter()
+        .filter(|(_, health, ..)| health.0 > 0)
+        .min_by(|(_, _, a, _), (_, _, b, _)| a.translation.x.total_cmp(&b.translation.x));
+    if let Some((enemy, ..)) = leftmost {
+        commands.entity(enemy).try_insert(CurrentTarget);
+    }
+}
+
+fn add_indicator(
+    mut commands: Commands,
+    enemies: Query<(Entity, &EnemyIntentYOffset), Added<EnemyIntentYOffset>>,
+) {
+    for (enemy, offset) in &enemies {
+        commands
+            .entity(enemy)
+            .with_child((
+                TargetIndicator,
+                Text2d::new("v"),
+                TextColor(Color::linear_rgb(1., 0.8, 0.2)),
+                Transform::from_xyz(0., offset.0 + 20., 2.),
+                Visibility::Hidden,
+            ));
+    }
+}
+
+fn display_indicator(
+    enemies: Query<(&Children, Has<CurrentTarget>), With<Enemy>>,
+    mut indicators: Query<&mut Visibility, With<TargetIndicator>>,
+) {
+    for (children, is_current) in &enemies {
+        let visibility = if is_current { Visibility::Inherited } else { Visibility::Hidden };
+        let mut indicators = indicators.iter_many_mut(children);
+        while let Some(mut indicator) = indicators.fetch_next() {
+            indicator.set_if_neq(visibility);
+        }
+    }
+}
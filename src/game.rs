@@ -1,6 +1,13 @@
 use bevy::prelude::*;
 
-use crate::{card::{actions::{ExecuteActions, FinishedExecution}, CardRedrawRequest}, grid::GridRefreshRequest};
+use crate::core::prelude::*;
+use crate::{card::{actions::ExecuteActions, tasks::{Task, Tasks, TasksFinished}, CardRedrawRequest}, combat_log::CombatLogEntry, encounter::EncounterEnemies, enemy::{self, Enemy}, grid::GridRefreshRequest, healthbar::{Health, MaxHealth, Shield}, layout::{DisplayGameView, DisplayMainMenu, GameView, SpeedButton}, player::Player, notification::{DisplayToast, NotificationKind}, simple_button::SimpleButtonLabel, status::{StatusEffect, StatusEffects}};
+
+/// Pause after the last action before the enemy turn starts.
+const POST_EXECUTE_DELAY_SECS: f32 = 1.0;
+
+/// Pause after the last enemy action before the next player turn starts.
+const POST_ENEMY_TURN_DELAY_SECS: f32 = 0.5;
 
 pub struct GamePlugin;
 
@@ -8,14 +15,33 @@ impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
         app
             .add_message::<StartCast>()
-            .init_state::<CastState>()
+            .add_message::<CycleGameSpeed>()
+            .add_message::<TurnStarted>()
+            .init_state::<TurnState>()
+            .init_resource::<GameSpeed>()
+            .add_systems(Update, cycle_game_speed.run_if(on_message::<CycleGameSpeed>))
+            .add_systems(Update, display_game_speed)
+            .add_systems(Update, apply_game_speed.after(cycle_game_speed))
+            .add_systems(Update, skip_tasks
+                .run_if(action_just_pressed(InputAction::Skip))
+                .run_if(in_state(TurnState::ExecuteActions).or(in_state(TurnState::EnemyTurn)))
+            )
             .add_systems(PostUpdate, start_cast
                 .run_if(on_message::<StartCast>)
-                .run_if(in_state(CastState::None))
+                .run_if(in_state(TurnState::PlayerTurn))
             )
-            .add_systems(PostUpdate, post_execute
-                .run_if(on_message::<FinishedExecution>)
-                .run_if(in_state(CastState::ExecuteActions))
+            .add_systems(PostUpdate, finish_phase
+                .run_if(on_message::<TasksFinished>)
+            )
+            .add_systems(OnEnter(TurnState::EnemyTurn), start_enemy_turn)
+            .add_systems(OnEnter(TurnState::EnemyTurn), start_status_turn::<Enemy>.before(enemy::enemy_turn))
+            .add_systems(OnExit(TurnState::EnemyTurn), end_status_turn::<Enemy>)
+            .add_systems(OnExit(TurnState::ExecuteActions), end_status_turn::<Player>)
+            .add_systems(OnEnter(TurnState::StartOfTurn), start_status_turn::<Player>.before(start_turn))
+            .add_systems(OnEnter(TurnState::StartOfTurn), start_turn)
+            .add_systems(PostUpdate, abort_turn
+                .run_if(on_message::<DisplayMainMenu>.or(on_message::<DisplayGameView>))
+                .run_if(not(in_state(TurnState::PlayerTurn)))
             );
     }
 }
@@ -23,29 +49,262 @@ impl Plugin for GamePlugin {
 #[derive(Message, Default)]
 pub struct StartCast;
 
+#[derive(Message, Default)]
+pub struct CycleGameSpeed;
+
+/// Multiplier applied to cast delays and animations.
+#[derive(Resource, Clone, Copy, PartialEq, Debug)]
+pub struct GameSpeed(pub f32);
+
+impl Default for GameSpeed {
+    fn default() -> Self {
+        GameSpeed(1.0)
+    }
+}
+
+impl GameSpeed {
+    const LEVELS: [f32; 3] = [1.0, 2.0, 4.0];
+
+    fn next(&self) -> Self {
+        let position = Self::LEVELS
+            .iter()
+            .position(|level| *level == self.0)
+            .unwrap_or(0);
+
+        GameSpeed(Self::LEVELS[(position + 1) % Self::LEVELS.len()])
+    }
+
+    pub fn label(&self) -> String {
+        format!("{}x", self.0)
+    }
+}
+
+/// Written at the start of every player turn, after the board and hand were refreshed.
+#[derive(Message, Default)]
+pub struct TurnStarted;
+
+/// Phases of a single turn, in the order they follow each other.
 #[derive(States, Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
-pub enum CastState {
+pub enum TurnState {
+    /// Tiles can be swapped until the player casts.
     #[default]
-    None,
+    PlayerTurn,
+    /// Card actions resolve through the game view's tasks.
     ExecuteActions,
+    /// Enemy actions resolve through the game view's tasks.
+    EnemyTurn,
+    /// Start-of-turn effects, the board and hand are refreshed.
+    StartOfTurn,
+    /// All enemies are defeated, waits for the player to continue.
+    Victory,
+    /// The player died, waits for the player to go back to the menu.
+    Defeat,
 }
 
 /// PostUpdate call that changes that moves to the next state with every update.
 fn start_cast(
-    mut next_cast_state: ResMut<NextState<CastState>>,
+    mut next_turn_state: ResMut<NextState<TurnState>>,
+    mut tasks: Single<&mut Tasks, With<GameView>>,
     mut writer: MessageWriter<ExecuteActions>,
 ) {
-    next_cast_state.set(CastState::ExecuteActions);
+    next_turn_state.set(TurnState::ExecuteActions);
+    tasks.insert(POST_EXECUTE_DELAY_SECS, Task::wait(u64::MAX));
     writer.write(ExecuteActions);
 }
 
-fn post_execute(
-    mut next_cast_state: ResMut<NextState<CastState>>,
+/// Enemies queue their own actions when entering the enemy turn.
+fn start_enemy_turn(
+    mut tasks: Single<&mut Tasks, With<GameView>>,
+) {
+    tasks.insert(POST_ENEMY_TURN_DELAY_SECS, Task::wait(u64::MAX));
+}
+
+/// Moves to the next phase once the game view's tasks have all been executed.
+fn finish_phase(
+    turn_state: Res<State<TurnState>>,
+    mut next_turn_state: ResMut<NextState<TurnState>>,
+    game_view: Single<Entity, With<GameView>>,
+    player: Single<&Health, With<Player>>,
+    encounters: Query<&EncounterEnemies>,
+    enemies: Query<Option<&Health>, With<Enemy>>,
+    mut reader: MessageReader<TasksFinished>,
+) {
+    if !reader.read().any(|finished| finished.0 == *game_view) {
+        return
+    }
+
+    // dead enemies may already be despawned by their death animation,
+    // enemies still loading their archetype have no health yet and count as alive
+    let spawned = encounters.iter().any(|encounter| encounter.0 > 0);
+    let defeated = spawned && enemies.iter().all(|health| health.is_some_and(|health| health.0 == 0));
+
+    match turn_state.get() {
+        TurnState::ExecuteActions if defeated => {
+            next_turn_state.set(TurnState::Victory)
+        },
+        TurnState::ExecuteActions => next_turn_state.set(TurnState::EnemyTurn),
+        TurnState::EnemyTurn if player.0 == 0 => next_turn_state.set(TurnState::Defeat),
+        TurnState::EnemyTurn if defeated => {
+            next_turn_state.set(TurnState::Victory)
+        },
+        TurnState::EnemyTurn => next_turn_state.set(TurnState::StartOfTurn),
+        _ => {},
+    }
+}
+
+/// Shield lasts until the start of the next player turn.
+/// A stunned player skips straight to the enemy turn.
+fn start_turn(
+    mut next_turn_state: ResMut<NextState<TurnState>>,
+    mut player: Single<(&Health, &mut Shield, &mut StatusEffects), With<Player>>,
     mut grid_writer: MessageWriter<GridRefreshRequest>,
     mut card_writer: MessageWriter<CardRedrawRequest>,
+    mut turn_writer: MessageWriter<TurnStarted>,
+    mut log: MessageWriter<CombatLogEntry>,
+    mut toasts: MessageWriter<DisplayToast>,
 ) {
-    next_cast_state.set(CastState::None);
+    let (health, ref mut shield, ref mut effects) = *player;
+    if health.0 == 0 {
+        next_turn_state.set(TurnState::Defeat);
+        return
+    }
+
+    shield.0 = 0;
     grid_writer.write(GridRefreshRequest);
     card_writer.write(CardRedrawRequest);
+    turn_writer.write(TurnStarted);
+
+    if effects.has(StatusEffect::Stun) {
+        effects.end_turn();
+        log.write(CombatLogEntry::named("Player", "stunned", 0));
+        toasts.write(DisplayToast {
+            text: "Stunned, turn skipped".into(),
+            kind: NotificationKind::Warning,
+        });
+        next_turn_state.set(TurnState::EnemyTurn);
+    } else {
+        next_turn_state.set(TurnState::PlayerTurn);
+    }
 }
 
+/// Damage and healing over time, applied before the owner acts.
+fn start_status_turn<T: Component>(
+    query: Query<(&Name, &mut Health, &MaxHealth, &mut StatusEffects), With<T>>,
+    mut log: MessageWriter<CombatLogEntry>,
+) {
+    for (name, mut health, max_health, mut effects) in query {
+        if health.0 == 0 || effects.stacks.is_empty() {
+            continue
+        }
+
+        let tick = effects.start_turn();
+        if tick.damage > 0 {
+            health.0 = health.0.saturating_sub(tick.damage);
+            log.write(CombatLogEntry::named(name, "status damage", tick.damage).with_health(health.0));
+        }
+        if tick.heal > 0 && health.0 > 0 {
+            health.0 = (health.0 + tick.heal).min(max_health.0);
+            log.write(CombatLogEntry::named(name, "regen", tick.heal).with_health(health.0));
+        }
+    }
+}
+
+/// Durations count down once the owner's turn is over.
+fn end_status_turn<T: Component>(
+    query: Query<&mut StatusEffects, With<T>>,
+) {
+    for mut effects in query {
+        if !effects.stacks.is_empty() {
+            effects.end_turn();
+        }
+    }
+}
+
+/// Leaving the game view drops its tasks, so the turn would never finish.
+/// Also leaves the victory and defeat screens.
+fn abort_turn(
+    mut next_turn_state: ResMut<NextState<TurnState>>,
+) {
+    next_turn_state.set(TurnState::PlayerTurn);
+}
+
+fn cycle_game_speed(
+    mut speed: ResMut<GameSpeed>,
+) {
+    *speed = speed.next();
+}
+
+fn display_game_speed(
+    speed: Res<GameSpeed>,
+    buttons: Query<(&Children, Ref<SpeedButton>)>,
+    mut labels: Query<&mut Text2d, With<SimpleButtonLabel>>,
+) {
+    for (children, button) in &buttons {
+        if !speed.is_changed() && !button.is_added() {
+            continue
+        }
+
+        let mut labels = labels.iter_many_mut(children);
+        while let Some(mut text) = labels.fetch_next() {
+            *text = Text2d::new(speed.label());
+        }
+    }
+}
+
+/// Task queues are spawned with the screens, so new ones pick up the current speed too.
+fn apply_game_speed(
+    speed: Res<GameSpeed>,
+    mut tasks: Query<&mut Tasks>,
+) {
+    for mut tasks in &mut tasks {
+        if tasks.speed() != speed.0 {
+            tasks.set_speed(speed.0);
+        }
+    }
+}
+
+/// Runs all pending actions of the phase at once, in their original order.
+fn skip_tasks(
+    mut tasks: Single<&mut Tasks, With<GameView>>,
+) {
+    tasks.skip();
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::state::app::StatesPlugin;
+
+    use super::*;
+
+    fn finish_execute_actions(app: &mut App) -> bool {
+        app.insert_state(TurnState::ExecuteActions);
+        let game_view = app.world_mut().query_filtered::<Entity, With<GameView>>().single(app.world()).unwrap();
+        app.world_mut().write_message(TasksFinished(game_view));
+        app.update();
+
+        matches!(app.world().resource::<NextState<TurnState>>(), NextState::Pending(TurnState::Victory))
+    }
+
+    #[test]
+    fn test_victory_after_death_animation() {
+        let mut app = App::new();
+        app
+            .add_plugins(StatesPlugin)
+            .add_message::<TasksFinished>()
+            .add_systems(Update, finish_phase);
+
+        app.world_mut().spawn(GameView);
+        app.world_mut().spawn((Player, Health(10)));
+        app.world_mut().spawn(EncounterEnemies(2));
+        let last = app.world_mut().spawn((Enemy, Health(0))).id();
+        let loading = app.world_mut().spawn(Enemy).id();
+
+        assert!(!finish_execute_actions(&mut app));
+
+        // the last enemy is killed and despawned once its death animation finishes
+        app.world_mut().entity_mut(loading).insert(Health(0));
+        app.world_mut().despawn(last);
+        app.world_mut().despawn(loading);
+        assert!(finish_execute_actions(&mut app));
+    }
+}
@@ -1,9 +1,10 @@
-use bevy::{input::common_conditions::{input_just_pressed, input_just_released, input_pressed}, platform::collections::HashMap, prelude::*};
+use bevy::{platform::collections::HashMap, prelude::*};
 use bevy_rand::prelude::*;
-use rand::{Rng, distr::{Distribution, StandardUniform}};
+use rand::{Rng, distr::{Distribution, StandardUniform}, seq::SliceRandom};
+use serde::Deserialize;
 
 use crate::core::prelude::*;
-use crate::{grid_highlight::GridHighlightRequest, scale_on_touch, tooltip_on_touch::TooltipOnTouch};
+use crate::{game::{GameSpeed, TurnState}, grid_highlight::GridHighlightRequest, notification::{DisplayToast, NotificationKind}, scale_on_touch, tooltip_on_touch::TooltipOnTouch};
 
 #[derive(Message, Default)]
 pub struct GridRefreshRequest;
@@ -31,6 +32,18 @@ impl GridConfig {
         self.tile_size.y * self.dimensions.1 as f32
     }
 
+    /// Adjacent cell in the swipe direction, `None` at the edge of the grid.
+    pub fn neighbour(&self, index: &Index, direction: SwipeDirection) -> Option<Index> {
+        let (x, y) = match direction {
+            SwipeDirection::Left => (index.x.checked_sub(1)?, index.y),
+            SwipeDirection::Right => (index.x + 1, index.y),
+            SwipeDirection::Down => (index.x, index.y.checked_sub(1)?),
+            SwipeDirection::Up => (index.x, index.y + 1),
+        };
+
+        (x < self.dimensions.0 && y < self.dimensions.1).then(|| Index::new(x, y))
+    }
+
     pub fn xy_position(&self, index: &Index) -> Vec2 {
         return vec2((self.dimensions.0 - 1) as f32, (self.dimensions.1 - 1) as f32) * self.tile_size * (-0.5) + vec2(index.x as f32, index.y as f32) * self.tile_size
     }
@@ -39,26 +52,88 @@ impl GridConfig {
 #[derive(Component)]
 pub struct GridTile;
 
-#[derive(Component, Clone, Copy, PartialEq)]
+#[derive(Component, Clone, Copy, PartialEq, Debug, Deserialize)]
 pub enum GridTileColor {
     Green,
     Red,
     Blue,
     Brown,
     Multicolor,
+    /// Placed by enemies, never matches and can't be moved.
+    Stone,
 }
 
 impl GridTileColor {
     pub fn is_matching(&self, other: &Self) -> bool {
+        if *self == GridTileColor::Stone || *other == GridTileColor::Stone {
+            return false
+        }
+
         self == other ||
         *self == GridTileColor::Multicolor || 
         *other == GridTileColor::Multicolor
     }
 }
 
+/// Mark left on a tile by an enemy, cleared when the board is refreshed.
+#[derive(Component, Clone, Copy, PartialEq, Debug)]
+pub enum GridTileEffect {
+    None,
+    /// Can't be moved.
+    Locked,
+    /// Recolored together with its row.
+    Recolored,
+    /// Requirement cell of a card moved here.
+    Scrambled,
+}
+
+impl GridTileEffect {
+    fn tint(&self) -> Color {
+        match *self {
+            GridTileEffect::None => Color::WHITE,
+            GridTileEffect::Locked => Color::linear_rgb(0.35, 0.35, 0.5),
+            GridTileEffect::Recolored => Color::linear_rgb(0.8, 0.65, 0.5),
+            GridTileEffect::Scrambled => Color::linear_rgb(1., 0.6, 1.),
+        }
+    }
+
+    fn description(&self) -> Option<&'static str> {
+        match *self {
+            GridTileEffect::None => None,
+            GridTileEffect::Locked => Some("Locked by an enemy, can't be moved this turn."),
+            GridTileEffect::Recolored => Some("Row recolored by an enemy."),
+            GridTileEffect::Scrambled => Some("A card's requirement was moved here by an enemy."),
+        }
+    }
+}
+
+fn is_movable(color: &GridTileColor, effect: &GridTileEffect) -> bool {
+    *color != GridTileColor::Stone && *effect != GridTileEffect::Locked
+}
+
+/// Change to the board applied when the next board is rolled.
+#[derive(Clone, Copy, Debug)]
+pub enum BoardEffect {
+    /// Recolor random tiles.
+    Recolor {
+        color: GridTileColor,
+        count: usize,
+    },
+    /// Recolor a random row.
+    RecolorRow(GridTileColor),
+    /// Lock random tiles in place.
+    Lock(usize),
+    /// Turn random tiles into stones.
+    Stone(usize),
+}
+
 #[derive(Resource)]
 struct PickedGridTile(Option<Entity>);
 
+/// Set when the picked tile was swapped by dragging, so the drag is not also a swipe.
+#[derive(Resource, Default)]
+struct SwappedByDrag(bool);
+
 #[derive(Component)]
 pub struct Grid;
 
@@ -66,6 +141,35 @@ pub struct Grid;
 pub struct GridData {
     moves_made: Vec<GridMove>,
     moves_limit: usize,
+    /// Extra moves granted for the next board.
+    bonus_moves: usize,
+    /// Moves taken away from the next board.
+    stolen_moves: usize,
+    /// Moves taken away from the current board, displayed next to the moves.
+    stolen_this_turn: usize,
+    /// Applied when the next board is rolled.
+    effects: Vec<BoardEffect>,
+}
+
+impl GridData {
+    pub fn add_bonus_moves(&mut self, moves: usize) {
+        self.bonus_moves += moves;
+    }
+
+    pub fn add_recolor(&mut self, color: GridTileColor, count: usize) {
+        self.effects.push(BoardEffect::Recolor {
+            color,
+            count,
+        });
+    }
+
+    pub fn add_effect(&mut self, effect: BoardEffect) {
+        self.effects.push(effect);
+    }
+
+    pub fn steal_moves(&mut self, moves: usize) {
+        self.stolen_moves += moves;
+    }
 }
 
 #[derive(Component, Deref, DerefMut)]
@@ -124,6 +228,7 @@ impl GridTileColor {
             GridTileColor::Blue => "blue.png",
             GridTileColor::Brown => "brown.png",
             GridTileColor::Multicolor => "multicolor.png",
+            GridTileColor::Stone => "stone.png",
         };
 
         format!("tiles/{}", name)
@@ -152,6 +257,7 @@ impl GridTileColor {
             GridTileColor::Blue => "Blue Tile",
             GridTileColor::Brown => "Brown Tile",
             GridTileColor::Multicolor => "Multicolor Tile",
+            GridTileColor::Stone => "Stone, can't be moved and never matches",
         }
     }
 
@@ -162,6 +268,7 @@ impl GridTileColor {
             GridTileColor::Blue => Color::linear_rgb(0., 0., 1.),
             GridTileColor::Brown => Color::linear_rgb(0.5, 0.5, 1.),
             GridTileColor::Multicolor => Color::linear_rgb(0., 0., 0.),
+            GridTileColor::Stone => Color::linear_rgb(0.3, 0.3, 0.3),
         }
     }
 }
@@ -182,15 +289,31 @@ impl Plugin for GridPlugin {
             .add_systems(Update, add_grid_tiles)
             .add_systems(Update, handle_refresh_request.run_if(on_message::<GridRefreshRequest>))
             .add_systems(Update, handle_reset_moves_request.run_if(on_message::<GridResetMovesRequest>))
-            .add_systems(Update, handle_pick.run_if(input_just_pressed(MouseButton::Left)))
-            .add_systems(Update, handle_drag.run_if(input_pressed(MouseButton::Left)))
-            .add_systems(Update, handle_release.run_if(input_just_released(MouseButton::Left)))
+            .add_systems(Update, handle_pick.run_if(action_just_pressed(InputAction::Pick)))
+            .add_systems(Update, handle_drag.run_if(action_pressed(InputAction::Pick)))
+            .add_systems(Update, handle_release
+                .run_if(action_just_released(InputAction::Pick).or(action_just_pressed(InputAction::Cancel)))
+            )
+            .add_systems(Update, undo_move
+                .run_if(action_just_pressed(InputAction::Undo))
+                .run_if(in_state(TurnState::PlayerTurn))
+            )
+            .add_systems(Update, pass_through_picked)
             .add_systems(Update, update_positions)
-            .add_systems(Update, swap.run_if(is_picked).run_if(just_touched::<GridTile>))
+            .add_systems(Update, swap
+                .run_if(is_picked)
+                .run_if(just_touched::<GridTile>)
+                .run_if(in_state(TurnState::PlayerTurn))
+            )
+            .add_systems(Update, swipe
+                .run_if(on_message::<Swipe>)
+                .run_if(in_state(TurnState::PlayerTurn))
+            )
             .add_systems(Update, update_grid_moves_label)
             .add_systems(Update, update_grid_tile_color)
             .insert_resource(self.config)
-            .insert_resource(PickedGridTile(None));
+            .insert_resource(PickedGridTile(None))
+            .init_resource::<SwappedByDrag>();
     }
 }
 
@@ -209,6 +332,10 @@ fn add_grid_tiles(
                 GridData {
                     moves_made: vec![],
                     moves_limit: 3,
+                    bonus_moves: 0,
+                    stolen_moves: 0,
+                    stolen_this_turn: 0,
+                    effects: vec![],
                 }
             ))
             .with_children(|parent| {
@@ -223,10 +350,13 @@ fn add_grid_tiles(
                             Name::new("Grid Tile"),
                             Transform::from_xyz(position.x, position.y, 0.),
                             tile_color,
+                            GridTileEffect::None,
                             index,
                             TouchArea {
                                 area: config.tile_size,
                             },
+                            PressArea,
+                            GestureArea,
                             scale_on_touch::ScaleOnTouch(2.0),
                             TooltipOnTouch(tile_color.tooltip_text().to_string())
                         )).id();
@@ -243,47 +373,116 @@ fn update_grid_tile_color(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     config: Res<GridConfig>,
-    tiles: Query<(Entity, &GridTileColor), (With<GridTile>, Changed<GridTileColor>)>,
+    tiles: Query<(Entity, &GridTileColor, &GridTileEffect), (With<GridTile>, Or<(Changed<GridTileColor>, Changed<GridTileEffect>)>)>,
 ) {
     tiles
         .into_iter()
-        .for_each(|(entity, tile_color)| {
+        .for_each(|(entity, tile_color, effect)| {
             let mut sprite = Sprite::from_image(asset_server.load(tile_color.sprite_name()));
             sprite.custom_size = Some(config.tile_size);
+            sprite.color = effect.tint();
+
+            let tooltip = match effect.description() {
+                Some(description) => format!("{}\n{}", tile_color.tooltip_text(), description),
+                None => tile_color.tooltip_text().to_string(),
+            };
+
             commands
                 .entity(entity)
                 .try_insert((
                     sprite,
-                    TooltipOnTouch(tile_color.tooltip_text().to_string())
+                    TooltipOnTouch(tooltip)
                 ));
         });
 }
 
 fn handle_refresh_request(
+    config: Res<GridConfig>,
     grids: Query<&mut GridData, With<Grid>>,
-    tiles: Query<&mut GridTileColor, With<GridTileColor>>,
+    mut tiles: Query<(&Index, &mut GridTileColor, &mut GridTileEffect), With<GridTile>>,
     mut rng: Single<&mut WyRand, With<GlobalRng>>,
     mut request: MessageWriter<GridHighlightRequest>,
 ) {
     println!("refreshed grid");
 
     tiles
-        .into_iter()
-        .for_each(|mut tile_color| {
+        .iter_mut()
+        .for_each(|(_, mut tile_color, mut effect)| {
             tile_color.set_if_neq(rng.random());
+            effect.set_if_neq(GridTileEffect::None);
         });
 
     grids
         .into_iter()
         .for_each(|mut data| {
+            let moves = 3 + data.bonus_moves;
             data.moves_made.clear();
-            data.moves_limit = 3;
-            
+            data.stolen_this_turn = data.stolen_moves.min(moves);
+            data.moves_limit = moves - data.stolen_this_turn;
+            data.bonus_moves = 0;
+            data.stolen_moves = 0;
+
+            for effect in data.effects.drain(..) {
+                apply_board_effect(effect, &config, &mut tiles, &mut rng);
+            }
         });
 
     request.write(GridHighlightRequest);
 }
 
+fn apply_board_effect<R: Rng + ?Sized>(
+    effect: BoardEffect,
+    config: &GridConfig,
+    tiles: &mut Query<(&Index, &mut GridTileColor, &mut GridTileEffect), With<GridTile>>,
+    rng: &mut R,
+) {
+    match effect {
+        BoardEffect::Recolor { color, count } => {
+            let mut targets: Vec<_> = tiles.iter_mut().collect();
+            targets.shuffle(rng);
+            targets
+                .into_iter()
+                .take(count)
+                .for_each(|(_, mut tile_color, _)| {
+                    tile_color.set_if_neq(color);
+                });
+        },
+        BoardEffect::RecolorRow(color) => {
+            let row = rng.random_range(0..config.dimensions.1);
+            tiles
+                .iter_mut()
+                .filter(|(index, _, _)| index.y == row)
+                .for_each(|(_, mut tile_color, mut effect)| {
+                    tile_color.set_if_neq(color);
+                    effect.set_if_neq(GridTileEffect::Recolored);
+                });
+        },
+        BoardEffect::Lock(count) => {
+            let mut targets: Vec<_> = tiles
+                .iter_mut()
+                .filter(|(_, tile_color, effect)| is_movable(tile_color, effect))
+                .collect();
+            targets.shuffle(rng);
+            targets
+                .into_iter()
+                .take(count)
+                .for_each(|(_, _, mut effect)| {
+                    effect.set_if_neq(GridTileEffect::Locked);
+                });
+        },
+        BoardEffect::Stone(count) => {
+            let mut targets: Vec<_> = tiles.iter_mut().collect();
+            targets.shuffle(rng);
+            targets
+                .into_iter()
+                .take(count)
+                .for_each(|(_, mut tile_color, _)| {
+                    tile_color.set_if_neq(GridTileColor::Stone);
+                });
+        },
+    }
+}
+
 fn handle_reset_moves_request(
     mut data: Single<&mut GridData, With<Grid>>,
 ) {
@@ -293,12 +492,13 @@ fn handle_reset_moves_request(
 }
 
 fn handle_pick(
-    tiles: Query<(Entity, &TouchState), With<GridTile>>,
+    tiles: Query<(Entity, &TouchState, &GridTileColor, &GridTileEffect), With<GridTile>>,
     mut picked: ResMut<PickedGridTile>,
+    mut swapped: ResMut<SwappedByDrag>,
 ) {
-
-    for (entity, state) in &tiles {
-        if state.is_touching() {
+    swapped.0 = false;
+    for (entity, state, color, effect) in &tiles {
+        if state.is_touching() && is_movable(color, effect) {
             picked.0 = Some(entity);
             return
         }
@@ -333,6 +533,25 @@ fn handle_release(
     picked.0 = None;
 }
 
+/// The dragged tile stays under the cursor, the tile below it has to be touchable for the swap.
+fn pass_through_picked(
+    mut commands: Commands,
+    tiles: Query<(Entity, Has<TouchPassThrough>), With<GridTile>>,
+    picked: Res<PickedGridTile>,
+) {
+    for (entity, pass_through) in &tiles {
+        match (is_this_picked(&entity, &picked), pass_through) {
+            (true, false) => {
+                commands.entity(entity).try_insert(TouchPassThrough);
+            },
+            (false, true) => {
+                commands.entity(entity).try_remove::<TouchPassThrough>();
+            },
+            _ => {},
+        }
+    }
+}
+
 fn is_this_picked(
     entity : &Entity,
     picked: &PickedGridTile,
@@ -345,12 +564,13 @@ fn is_this_picked(
 
 fn update_positions(
     time: Res<Time>,
+    speed: Res<GameSpeed>,
     mut tiles: Query<(Entity, &mut Transform, &Index), With<GridTile>>,
     config: Res<GridConfig>,
     picked: Res<PickedGridTile>,
 ) {
     for (entity, mut transform, index) in &mut tiles {
-        let delta = time.delta_secs();
+        let delta = time.delta_secs() * speed.0;
 
         if is_this_picked(&entity, &picked) {
             continue;
@@ -378,16 +598,18 @@ fn update_positions(
 
 fn swap(
     mut grid: Single<(&mut GridData, &mut GridTileByIndex)>,
-    mut tiles: Query<(Entity, &TouchState, &mut Index, &GridTileColor), (With<GridTile>, Changed<TouchState>)>,
+    mut tiles: Query<(Entity, &TouchState, &mut Index, &GridTileColor, &GridTileEffect), (With<GridTile>, Changed<TouchState>)>,
     mut picked: ResMut<PickedGridTile>,
+    mut swapped: ResMut<SwappedByDrag>,
     mut request: MessageWriter<GridHighlightRequest>,
+    mut toasts: MessageWriter<DisplayToast>,
 ) {
     println!("swap");
 
     // get a sprite below cursor which is not our current Dragged
     let entity = || -> Option<Entity> {
-        for (entity, touch_state, _, _) in &tiles {
-            if touch_state.is_just_touched() && !is_this_picked(&entity, &picked) {
+        for (entity, touch_state, _, color, effect) in &tiles {
+            if touch_state.is_just_touched() && !is_this_picked(&entity, &picked) && is_movable(color, effect) {
                 return Some(entity)
             }
         }
@@ -396,7 +618,7 @@ fn swap(
 
     match (entity, picked.0) {
         (Some(entity), Some(d)) => {
-            if let Ok(mut ok) = tiles.get_many_mut([entity, d]) {
+            if let Ok([a, b]) = tiles.get_many_mut([entity, d]) {
                 let (ref mut grid, ref mut tiles_by_index) = *grid;
 
                 if grid.moves_made.len() == grid.moves_limit {
@@ -404,20 +626,12 @@ fn swap(
                 }
                 //grid.moves_made += 1;
 
-                let index_a = ok[0].2.clone();
-                let index_b = ok[1].2.clone();
-                ok[0].2.assign(&index_b);
-                ok[1].2.assign(&index_a);
-                tiles_by_index.insert(index_a, ok[1].0);
-                tiles_by_index.insert(index_b, ok[0].0);
-
-                let grid_move = GridMove {
-                    tile_a: (index_a, ok[0].3.clone()),
-                    tile_b: (index_b, ok[1].3.clone())
-                };
-                grid.moves_made.push(grid_move);
+                let (entity_a, _, mut index_a, color_a, _) = a;
+                let (entity_b, _, mut index_b, color_b, _) = b;
+                swap_tiles(grid, tiles_by_index, (entity_a, &mut index_a, *color_a), (entity_b, &mut index_b, *color_b), &mut toasts);
                 
                 picked.0 = None;
+                swapped.0 = true;
                 request.write(GridHighlightRequest);
 
             }
@@ -426,13 +640,113 @@ fn swap(
     }
 }
 
+/// Records the move and swaps the indices of two tiles.
+fn swap_tiles(
+    grid: &mut GridData,
+    tiles_by_index: &mut GridTileByIndex,
+    (entity_a, index_a, color_a): (Entity, &mut Index, GridTileColor),
+    (entity_b, index_b, color_b): (Entity, &mut Index, GridTileColor),
+    toasts: &mut MessageWriter<DisplayToast>,
+) {
+    let old_a = *index_a;
+    let old_b = *index_b;
+    index_a.assign(&old_b);
+    index_b.assign(&old_a);
+    tiles_by_index.insert(old_a, entity_b);
+    tiles_by_index.insert(old_b, entity_a);
+
+    let grid_move = GridMove {
+        tile_a: (old_a, color_a),
+        tile_b: (old_b, color_b)
+    };
+    grid.moves_made.push(grid_move);
+    if grid.moves_made.len() == grid.moves_limit {
+        toasts.write(DisplayToast {
+            text: "No moves left".into(),
+            kind: NotificationKind::Info,
+        });
+    }
+}
+
+/// Swaps the tiles of the last move back and gives the move back.
+fn undo_move(
+    mut grid: Single<(&mut GridData, &mut GridTileByIndex)>,
+    mut tiles: Query<&mut Index, With<GridTile>>,
+    picked: Res<PickedGridTile>,
+    mut request: MessageWriter<GridHighlightRequest>,
+) {
+    let (ref mut grid, ref mut tiles_by_index) = *grid;
+    if picked.0.is_some() {
+        return
+    }
+
+    let Some(GridMove { tile_a: (index_a, _), tile_b: (index_b, _) }) = grid.moves_made.pop() else {
+        return
+    };
+
+    let (Some(&entity_a), Some(&entity_b)) = (tiles_by_index.get(&index_a), tiles_by_index.get(&index_b)) else {
+        return
+    };
+
+    if let Ok([mut a, mut b]) = tiles.get_many_mut([entity_a, entity_b]) {
+        a.assign(&index_b);
+        b.assign(&index_a);
+        tiles_by_index.insert(index_a, entity_b);
+        tiles_by_index.insert(index_b, entity_a);
+        request.write(GridHighlightRequest);
+    }
+}
+
+/// Swipes swap a tile with its neighbour, like dragging it there.
+fn swipe(
+    config: Res<GridConfig>,
+    mut grid: Single<(&mut GridData, &mut GridTileByIndex)>,
+    mut tiles: Query<(&mut Index, &GridTileColor, &GridTileEffect), With<GridTile>>,
+    swapped: Res<SwappedByDrag>,
+    mut reader: MessageReader<Swipe>,
+    mut request: MessageWriter<GridHighlightRequest>,
+    mut toasts: MessageWriter<DisplayToast>,
+) {
+    let (ref mut grid, ref mut tiles_by_index) = *grid;
+    for swipe in reader.read() {
+        if swapped.0 || grid.moves_made.len() == grid.moves_limit {
+            continue
+        }
+
+        let Some(neighbour) = tiles
+            .get(swipe.entity)
+            .ok()
+            .and_then(|(index, ..)| config.neighbour(index, swipe.direction))
+            .and_then(|index| tiles_by_index.get(&index).copied()) else {
+            continue
+        };
+
+        let Ok([a, b]) = tiles.get_many_mut([swipe.entity, neighbour]) else {
+            continue
+        };
+
+        let (mut index_a, color_a, effect_a) = a;
+        let (mut index_b, color_b, effect_b) = b;
+        if !is_movable(color_a, effect_a) || !is_movable(color_b, effect_b) {
+            continue
+        }
+
+        swap_tiles(grid, tiles_by_index, (swipe.entity, &mut index_a, *color_a), (neighbour, &mut index_b, *color_b), &mut toasts);
+        request.write(GridHighlightRequest);
+    }
+}
+
 // TODO: modify so it doesn't use ChildOf
 fn update_grid_moves_label(
     grid: Single<&GridData>,
     mut labels: Query<&mut Text2d, With<GridMovesLabel>>,
 ) {
     for mut text in &mut labels {
-        *text = Text2d::new(format!("moves {}/{}", grid.moves_made.len(), grid.moves_limit));
+        let mut label = format!("moves {}/{}", grid.moves_made.len(), grid.moves_limit);
+        if grid.stolen_this_turn > 0 {
+            label += &format!(" (-{} stolen)", grid.stolen_this_turn);
+        }
+        *text = Text2d::new(label);
     }
 }
 
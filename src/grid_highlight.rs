@@ -1,6 +1,6 @@
 use bevy::{platform::collections::HashMap, prelude::*};
 
-use crate::{grid::{GridConfig, GridTile, GridTileByIndex, GridTileColor, Index}, layout::ContentView};
+use crate::{card::CardScoring, grid::{GridConfig, GridTile, GridTileByIndex, GridTileColor, Index}, layout::ContentView};
 
 #[derive(Message)]
 pub struct GridHighlightRequest;
@@ -9,6 +9,7 @@ pub struct GridHighlightRequest;
 #[derive(Component, Default)]
 pub struct GridHighlightsState {
     pub highlights_by_side: HashMap<GridTileHighlightSide, HashMap<Index, GridTileColor>>,
+    pub scoring_by_side: HashMap<GridTileHighlightSide, CardScoring>,
 }
 
 pub struct GridHighlightPlugin;
@@ -16,7 +17,7 @@ pub struct GridHighlightPlugin;
 #[derive(Component)]
 pub struct GridTileHighlight;
 
-#[derive(PartialEq, Hash, Eq)]
+#[derive(Clone, Copy, PartialEq, Hash, Eq)]
 pub enum GridTileHighlightSide {
     Left,
     Bottom,
@@ -68,6 +69,23 @@ fn highlight_grid(
         });
 
     for (side, indexes) in &state.highlights_by_side {
+        let matched = indexes
+            .iter()
+            .filter(|(index, expected_color)| {
+                tile_by_index
+                    .get(*index)
+                    .and_then(|entity| tiles.get(*entity).ok())
+                    .map(|(_, color)| expected_color.is_matching(color))
+                    .unwrap_or(false)
+            })
+            .count() as u64;
+
+        // matched tiles of a card that doesn't score yet are drawn faded
+        let is_scoring = state.scoring_by_side
+            .get(side)
+            .map(|scoring| scoring.points(matched, indexes.len() as u64) > 0)
+            .unwrap_or(true);
+
         for (index, expected_color) in indexes {
             if let Some(tile_entity) = tile_by_index.get(index) {
                 if let Some((_transform, tile_color)) = tiles.get(*tile_entity).ok() {
@@ -84,6 +102,9 @@ fn highlight_grid(
         
                     let mut sprite = Sprite::from_image(asset_server.load(filename));
                     sprite.custom_size = Some(config.tile_size);
+                    if !is_scoring {
+                        sprite.color = Color::linear_rgba(1., 1., 1., 0.5);
+                    }
 
                     let bundle = (
                         GridTileHighlight,
@@ -1,12 +1,29 @@
+use std::ops::DerefMut;
+
 use bevy::prelude::*;
 
+use crate::damage::DamageDealt;
+use crate::game::GameSpeed;
+
+/// Delay before the damage trail starts draining.
+const TRAIL_DELAY_SECS: f32 = 0.4;
+
+/// Fraction of the bar the damage trail drains per second.
+const TRAIL_DRAIN_PER_SEC: f32 = 0.5;
+
+/// How long the healed part of the bar flashes.
+const HEAL_FLASH_SECS: f32 = 0.6;
+
 pub struct HealthbarPlugin;
 
 impl Plugin for HealthbarPlugin {
     fn build(&self, app: &mut App) {
         app
             .add_systems(Update, draw_healthbar)
-            .add_systems(Update, update_healthbar);
+            .add_systems(Update, update_healthbar.after(draw_healthbar))
+            .add_systems(Update, animate_healthbar.after(update_healthbar))
+            .add_systems(Update, display_effectiveness.run_if(on_message::<DamageDealt>))
+            .add_systems(Update, hide_effectiveness);
     }
 }
 
@@ -16,24 +33,101 @@ pub struct Health(pub u64);
 #[derive(Component, Deref, DerefMut)]
 pub struct MaxHealth(pub u64);
 
+/// Absorbs damage before it reaches `Health`.
+#[derive(Component, Deref, DerefMut, Default)]
+pub struct Shield(pub u64);
+
 #[derive(Component)]
 pub struct Healthbar;
 
 #[derive(Component, Deref, DerefMut)]
 pub struct HealthbarYOffset(pub f32);
 
+/// Fractions of the maximum health marked on the healthbar, e.g. boss phases.
 #[derive(Component)]
-struct HealthbarBar;
+pub struct HealthbarSegments(pub Vec<f32>);
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum HealthbarTextFormat {
+    /// "80/100"
+    Fraction,
+    /// "80%"
+    Percent,
+}
+
+/// Look of the healthbar, the default is used when missing.
+#[derive(Component, Clone)]
+pub struct HealthbarStyle {
+    pub size: Vec2,
+    pub health_color: Color,
+    pub background_color: Color,
+    pub trail_color: Color,
+    pub shield_color: Color,
+    pub heal_color: Color,
+    /// Width of the border around the bar, none when zero.
+    pub border: f32,
+    pub border_color: Color,
+    pub text_format: HealthbarTextFormat,
+}
+
+impl Default for HealthbarStyle {
+    fn default() -> Self {
+        HealthbarStyle {
+            size: Vec2::new(200., 20.),
+            health_color: Color::linear_rgb(1., 0., 0.),
+            background_color: Color::linear_rgb(0.15, 0.02, 0.02),
+            trail_color: Color::linear_rgb(1., 0.85, 0.3),
+            shield_color: Color::linear_rgba(0.4, 0.7, 1., 0.8),
+            heal_color: Color::linear_rgb(0.4, 1., 0.4),
+            border: 2.,
+            border_color: Color::BLACK,
+            text_format: HealthbarTextFormat::Fraction,
+        }
+    }
+}
+
+impl HealthbarStyle {
+    fn text(&self, health: u64, max_health: u64, shield: u64) -> String {
+        let text = match self.text_format {
+            HealthbarTextFormat::Fraction => format!("{}/{}", health, max_health),
+            HealthbarTextFormat::Percent => format!("{}%", (fraction(health, max_health) * 100.).round()),
+        };
+
+        if shield > 0 {
+            format!("{} +{}", text, shield)
+        } else {
+            text
+        }
+    }
+}
+
+/// Displayed fractions, eased towards the current health.
+#[derive(Component)]
+struct HealthbarState {
+    health: f32,
+    trail: f32,
+    trail_delay: Timer,
+    /// Part of the bar gained by the last heal.
+    flash: (f32, f32),
+    flash_timer: Timer,
+}
 
 #[derive(Component)]
 struct HealthbarText;
 
+/// "crit!", "weak!" or "resisted" label, cleared when the timer finishes.
+#[derive(Component)]
+struct HealthbarEffectiveness(Timer);
+
 /// Ids of child components of the healthbar.
 #[derive(Component)]
 struct HealthbarComponents {
-    //background_bar: Entity,
     bar: Entity,
+    trail: Entity,
+    shield: Entity,
+    flash: Entity,
     text: Entity,
+    effectiveness: Entity,
 }
 
 // progress range 0.5
@@ -46,74 +140,216 @@ fn rect_x_width(progress: f32, max_width: f32) -> f32 {
     progress * max_width
 }
 
-fn health_text(health: u64, max_health: u64) -> String {
-    format!("{}/{}", health, max_health)
+fn fraction(amount: u64, max_health: u64) -> f32 {
+    ((amount as f64 / max_health.max(1) as f64) as f32).clamp(0., 1.)
+}
+
+/// Resizes a sprite of the bar to cover the fractions between `start` and `end`.
+/// Only writes when the segment moved, so resting bars are not marked as changed.
+fn set_segment(mut sprite: impl DerefMut<Target = Sprite>, mut transform: impl DerefMut<Target = Transform>, start: f32, end: f32, size: Vec2) {
+    let end = end.max(start);
+    let custom_size = Some(Vec2::new(rect_x_width(end - start, size.x), size.y));
+    let x = rect_x_center(end - start, size.x) + start * size.x;
+
+    if sprite.custom_size != custom_size {
+        sprite.custom_size = custom_size;
+    }
+    if transform.translation.x != x {
+        transform.translation.x = x;
+    }
 }
 
 fn draw_healthbar(
     mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    query: Query<(Entity, &Health, &MaxHealth, Option<&HealthbarYOffset>), Added<Healthbar>>
+    query: Query<(Entity, &Health, &MaxHealth, Option<&Shield>, Option<&HealthbarStyle>, Option<&HealthbarYOffset>, Option<&HealthbarSegments>), Added<Healthbar>>
 ) {
-    for (entity, health, max_health, y_offset) in query {
-        let progress = ((health.0 as f64 / max_health.0 as f64) as f32).max(0.).min(1.);
-        let max_width = 200.0;
-        let height = 20.0;
-        let rectangle = Rectangle::new(rect_x_width(progress, max_width), height);
+    for (entity, health, max_health, shield, style, y_offset, segments) in query {
+        let style = style.cloned().unwrap_or_default();
+        let progress = fraction(health.0, max_health.0);
+        let shield = shield.map(|shield| shield.0).unwrap_or(0);
         let y_offset = y_offset.map(|y| y.0).unwrap_or(0.);
+        let size = style.size;
+
+        commands
+            .entity(entity)
+            .with_child((
+                Sprite::from_color(style.border_color, size + Vec2::splat(style.border * 2.)),
+                Transform::from_xyz(0., y_offset, 0.8),
+            ))
+            .with_child((
+                Sprite::from_color(style.background_color, size),
+                Transform::from_xyz(0., y_offset, 0.9),
+            ));
+
+        let mut segment = |color: Color, start: f32, end: f32, z: f32| {
+            let mut sprite = Sprite::from_color(color, size);
+            let mut transform = Transform::from_xyz(0., y_offset, z);
+            set_segment(&mut sprite, &mut transform, start, end, size);
+            commands.spawn((sprite, transform)).id()
+        };
 
-        let bar = commands.spawn((
-            HealthbarBar,
-            Mesh2d(meshes.add(rectangle)),
-            MeshMaterial2d(materials.add(Color::linear_rgb(1.0, 0.0, 0.0))),
-            Transform::from_xyz(rect_x_center(progress, max_width), y_offset, 1.),
-            Visibility::Inherited,
-            )).id();
+        let trail = segment(style.trail_color, 0., progress, 0.95);
+        let bar = segment(style.health_color, 0., progress, 1.);
+        let shield_end = (progress + fraction(shield, max_health.0)).min(1.);
+        let shield_bar = segment(style.shield_color, progress, shield_end, 1.05);
+        let flash = segment(style.heal_color.with_alpha(0.), progress, progress, 1.1);
 
         let text = commands.spawn((
             HealthbarText,
-            Text2d::new(health_text(health.0, max_health.0)),
-            Transform::from_xyz(0., y_offset, 1.),
+            Text2d::new(style.text(health.0, max_health.0, shield)),
+            Transform::from_xyz(0., y_offset, 1.2),
         )).id();
 
+        let effectiveness = commands.spawn((
+            HealthbarEffectiveness(Timer::from_seconds(1.0, TimerMode::Once)),
+            Text2d::new(""),
+            Transform::from_xyz(0., y_offset - size.y, 1.),
+        )).id();
+
+        for fraction in segments.map(|segments| segments.0.as_slice()).unwrap_or_default() {
+            commands
+                .entity(entity)
+                .with_child((
+                    Sprite::from_color(style.border_color, Vec2::new(2., size.y)),
+                    Transform::from_xyz((fraction - 0.5) * size.x, y_offset, 1.15),
+                ));
+        }
+
         commands
             .entity(entity)
-            .add_children(&[bar, text])
-            .try_insert(HealthbarComponents {
-                bar,
-                text,
-            });
+            .add_children(&[trail, bar, shield_bar, flash, text, effectiveness])
+            .try_insert((
+                HealthbarComponents {
+                    bar,
+                    trail,
+                    shield: shield_bar,
+                    flash,
+                    text,
+                    effectiveness,
+                },
+                HealthbarState {
+                    health: progress,
+                    trail: progress,
+                    trail_delay: Timer::from_seconds(TRAIL_DELAY_SECS, TimerMode::Once),
+                    flash: (progress, progress),
+                    flash_timer: Timer::from_seconds(HEAL_FLASH_SECS, TimerMode::Once),
+                },
+                style,
+            ));
     }
 }
 
+/// Damage leaves a trail behind, heals flash the gained part of the bar.
 fn update_healthbar(
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut bars: Query<(&mut Transform, &Mesh2d), With<HealthbarBar>>,
+    mut sprites: Query<(&mut Sprite, &mut Transform), Without<HealthbarText>>,
     mut texts: Query<&mut Text2d, With<HealthbarText>>,
-    query: Query<(&Health, &MaxHealth, &HealthbarComponents, Option<&HealthbarYOffset>), (Changed<Health>, With<Healthbar>)>,
+    query: Query<(Ref<Health>, &MaxHealth, Option<Ref<Shield>>, &HealthbarStyle, &HealthbarComponents, &mut HealthbarState)>,
 ) {
-    for (health, max_health, components, y_offset) in query {
-        // update text
-        if let Some(mut text) = texts.get_mut(components.text).ok() {
-            text.0 = health_text(health.0, max_health.0);
+    for (health, max_health, shield, style, components, mut state) in query {
+        let shield_changed = shield.as_ref().is_some_and(|shield| shield.is_changed());
+        if !health.is_changed() && !shield_changed {
+            continue
         }
 
-        if let Some((mut transform, mesh2d)) = bars.get_mut(components.bar).ok() {
-            let progress = ((health.0 as f64 / max_health.0 as f64) as f32).max(0.).min(1.);
-            let max_width = 200.0;
-            let height = 20.0;
+        let progress = fraction(health.0, max_health.0);
+        let shield = shield.map(|shield| shield.0).unwrap_or(0);
 
-            let y_offset = y_offset.map(|y| y.0).unwrap_or(0.);
-            *transform = Transform::from_xyz(rect_x_center(progress, max_width), y_offset, 1.);
+        if progress > state.health {
+            state.flash = (state.health, progress);
+            state.flash_timer.reset();
+            state.trail = progress;
+        } else if progress < state.health {
+            state.trail_delay.reset();
+        }
+        state.health = progress;
+
+        if let Ok(mut text) = texts.get_mut(components.text) {
+            text.0 = style.text(health.0, max_health.0, shield);
+        }
+
+        if let Ok((mut sprite, mut transform)) = sprites.get_mut(components.bar) {
+            set_segment(sprite.reborrow(), transform.reborrow(), 0., progress, style.size);
+        }
+
+        if let Ok((mut sprite, mut transform)) = sprites.get_mut(components.shield) {
+            let end = (progress + fraction(shield, max_health.0)).min(1.);
+            set_segment(sprite.reborrow(), transform.reborrow(), progress, end, style.size);
+        }
+    }
+}
+
+fn animate_healthbar(
+    time: Res<Time>,
+    speed: Res<GameSpeed>,
+    mut sprites: Query<(&mut Sprite, &mut Transform)>,
+    query: Query<(&HealthbarStyle, &HealthbarComponents, &mut HealthbarState)>,
+) {
+    let delta = time.delta().mul_f32(speed.0);
+    for (style, components, mut state) in query {
+        if state.trail > state.health && state.trail_delay.tick(delta).is_finished() {
+            state.trail = (state.trail - TRAIL_DRAIN_PER_SEC * delta.as_secs_f32()).max(state.health);
+        }
+
+        if let Ok((mut sprite, mut transform)) = sprites.get_mut(components.trail) {
+            set_segment(sprite.reborrow(), transform.reborrow(), 0., state.trail, style.size);
+        }
+
+        if state.flash_timer.is_finished() {
+            continue
+        }
+
+        state.flash_timer.tick(delta);
+        if let Ok((mut sprite, mut transform)) = sprites.get_mut(components.flash) {
+            let (start, end) = state.flash;
+            set_segment(sprite.reborrow(), transform.reborrow(), start, end, style.size);
+            sprite.color = style.heal_color.with_alpha(state.flash_timer.fraction_remaining());
+        }
+    }
+}
+
+fn display_effectiveness(
+    healthbars: Query<&HealthbarComponents>,
+    mut labels: Query<(&mut Text2d, &mut HealthbarEffectiveness)>,
+    mut reader: MessageReader<DamageDealt>,
+) {
+    for dealt in reader.read() {
+        // names the type, so the player learns which element the enemy is weak to
+        let crit = dealt.result.is_crit.then(|| "crit!".to_string());
+        let effectiveness = dealt.result.effectiveness
+            .label()
+            .map(|label| format!("{:?} {}", dealt.damage_type, label));
+        let label = [crit, effectiveness]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        if label.is_empty() {
+            continue
+        }
+
+        if let Ok(components) = healthbars.get(dealt.target)
+            && let Ok((mut text, mut effectiveness)) = labels.get_mut(components.effectiveness)
+        {
+            text.0 = label;
+            effectiveness.0.reset();
+        }
+    }
+}
 
-            if let Some(mesh) = meshes.get_mut(mesh2d) {
-                *mesh = Rectangle::new(rect_x_width(progress, max_width), height).into();
-            }
+fn hide_effectiveness(
+    time: Res<Time>,
+    speed: Res<GameSpeed>,
+    labels: Query<(&mut Text2d, &mut HealthbarEffectiveness)>,
+) {
+    for (mut text, mut effectiveness) in labels {
+        if effectiveness.0.tick(time.delta().mul_f32(speed.0)).just_finished() {
+            text.0.clear();
         }
     }
 }
 
+#[cfg(test)]
 mod tests {
     
     #[test]
@@ -124,5 +360,19 @@ mod tests {
         assert_eq!(-90.0, rect_x_center(0.1, 200.0));
         assert_eq!(-80.0, rect_x_center(0.2, 200.0));
     }
-}
 
+    #[test]
+    fn test_healthbar_text() {
+        use super::*;
+
+        let style = HealthbarStyle::default();
+        assert_eq!("80/100", style.text(80, 100, 0));
+        assert_eq!("80/100 +5", style.text(80, 100, 5));
+
+        let style = HealthbarStyle {
+            text_format: HealthbarTextFormat::Percent,
+            ..default()
+        };
+        assert_eq!("33%", style.text(1, 3, 0));
+    }
+}
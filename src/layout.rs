@@ -1,7 +1,10 @@
 use bevy::{prelude::*, sprite::Anchor};
-use crate::card;
-use crate::enemy::Enemy;
-use crate::game::StartCast;
+use crate::card::{self, tasks::Tasks};
+use crate::core::prelude::*;
+use crate::combat_log::CombatLogView;
+use crate::encounter::{EncounterRewards, EnemiesView};
+use crate::game::{CycleGameSpeed, StartCast};
+use crate::player::{PlayerHealthLabel, PlayerStatusView};
 use crate::grid::{Grid, GridMovesLabel, GridRefreshRequest, GridResetMovesRequest};
 use crate::score::ScoreLabel;
 use crate::simple_button::{button_system, SimpleButton};
@@ -27,6 +30,10 @@ impl Plugin for LayoutPlugin {
             .add_systems(Update, button_system::<RedrawButton, card::CardRedrawRequest>)
 
             .add_systems(Update, button_system::<CastButton, StartCast>)
+            .add_systems(Update, action_message::<GridRefreshRequest>.run_if(action_just_pressed(InputAction::Refresh)))
+            .add_systems(Update, action_message::<card::CardRedrawRequest>.run_if(action_just_pressed(InputAction::Redraw)))
+            .add_systems(Update, action_message::<StartCast>.run_if(action_just_pressed(InputAction::Cast)))
+            .add_systems(Update, button_system::<SpeedButton, CycleGameSpeed>)
 
             .add_systems(Update, display_main_menu.run_if(on_message::<DisplayMainMenu>))
             .add_systems(Update, display_game_view.run_if(on_message::<DisplayGameView>));
@@ -36,6 +43,10 @@ impl Plugin for LayoutPlugin {
 #[derive(Component)]
 pub struct RootView;
 
+/// Root of the game screen, owns the screen's task queue.
+#[derive(Component)]
+pub struct GameView;
+
 #[derive(Component)]
 pub struct TopBarView;
 
@@ -48,6 +59,9 @@ pub struct BottomBarView;
 #[derive(Component)]
 pub struct CastButton;
 
+#[derive(Component)]
+pub struct SpeedButton;
+
 #[derive(Message, Default)]
 pub struct DisplayMainMenu;
 
@@ -120,6 +134,9 @@ fn display_game_view(
 
             // top one can be removed?
             root.spawn((
+                GameView,
+                Tasks::default(),
+                EncounterRewards::default(),
                 Sprite::from_color(Color::WHITE, Vec2::new(800., 600.)),
                 Transform::from_xyz(0., 0., 0.),
                 children![(
@@ -131,12 +148,11 @@ fn display_game_view(
                     children![
                         SimpleButton::create(BackButton, "back", (-400. + 48. + 8., -24. - 8.).into()),
                         SimpleButton::create(RefreshButton, "refresh", (400. - 48. - 8., -24. - 8.).into()),
+                        SimpleButton::create(SpeedButton, "1x", (400. - 96. - 48. - 16., -24. - 8.).into()),
                         (
+                            EnemiesView,
                             Transform::from_xyz(0., -128. -20. + 64., 0.),
-                            children![
-                                Enemy,
-                                Transform::from_xyz(0., 0., 0.),
-                            ]
+                            Visibility::Inherited,
                         )
                     ]
                 ),(
@@ -159,6 +175,17 @@ fn display_game_view(
                     ), (
                         TooltipView,
                         Transform::from_xyz(300., 64., 0.)
+                    ), (
+                        PlayerHealthLabel,
+                        Text2d::new(""),
+                        Transform::from_xyz(-300., -150., 5.),
+                    ), (
+                        PlayerStatusView,
+                        Transform::from_xyz(-300., -180., 5.),
+                        Visibility::Inherited,
+                    ), (
+                        CombatLogView,
+                        Transform::from_xyz(-390., 120., 5.),
                     )]
                 ),(
                     BottomBarView,
@@ -173,6 +200,11 @@ fn display_game_view(
                     ), 
                         SimpleButton::create(RedrawButton, "redraw", (-400. + 48. + 8., 32.).into()),
                         SimpleButton::create(CastButton, "cast", (400. - 48. - 8., 32.).into()),
+                        (
+                            card::CastPreviewLabel,
+                            Text2d::new(""),
+                            Transform::from_xyz(400. - 48. - 8., 80., 0.),
+                        ),
                     ]
                 )
                 ]
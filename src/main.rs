@@ -10,42 +10,54 @@ mod score;
 mod animated_sprite;
 
 mod core;
+mod combat_log;
+mod damage;
+mod encounter;
 mod enemy;
 mod game;
 mod healthbar;
 mod notification;
+mod player;
+mod status;
 
 use animated_sprite::AnimatedSpritePlugin;
-use bevy::prelude::*;
+use bevy::{prelude::*, sprite::SpritePickingCamera};
 use bevy_rand::{self, plugin::EntropyPlugin, prelude::WyRand};
-use bevy::input::common_conditions::input_toggle_active;
 use bevy_egui::{EguiPlugin};
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
-use bevy_write_after::WriteAfterPlugin;
 use card::{actions::ActionPlugin, CardPlugin};
+use combat_log::CombatLogPlugin;
+use encounter::EncounterPlugin;
 use enemy::EnemyPlugin;
 use game::GamePlugin;
 use grid::{GridConfig, GridPlugin};
 use grid_highlight::GridHighlightPlugin;
 use healthbar::HealthbarPlugin;
 use layout::LayoutPlugin;
+use notification::NotificationPlugin;
+use player::PlayerPlugin;
 use core::prelude::*;
 use scale_on_touch::ScaleOnTouchPlugin;
 use score::ScorePlugin;
+use status::StatusPlugin;
 use styles::StylePlugin;
 use tooltip_on_touch::TooltipOnTouchPlugin;
 
 fn main() {
+    let recording = InputRecordingPlugin::from_args();
+
     App::new()
         .add_plugins(DefaultPlugins.set(ImagePlugin::default_nearest()))
         .add_plugins(EguiPlugin::default())
-        .add_plugins(WorldInspectorPlugin::default().run_if(input_toggle_active(true, KeyCode::Escape)))
-        .add_plugins(EntropyPlugin::<WyRand>::default())
-        .add_plugins(WriteAfterPlugin)
+        .add_plugins(WorldInspectorPlugin::default().run_if(action_toggle_active(true, InputAction::ToggleInspector)))
+        .add_plugins(EntropyPlugin::<WyRand>::with_seed(recording.seed().to_le_bytes()))
+        .add_plugins(recording)
+        .add_plugins(InputMapPlugin)
         .add_plugins(MousePlugin)
         .add_plugins(StylePlugin)
         .add_plugins(TouchPlugin)
         .add_plugins(PressPlugin)
+        .add_plugins(GesturePlugin)
         .add_plugins(AnimatedSpritePlugin)
         .add_plugins(ScaleOnTouchPlugin)
         .add_plugins(TooltipOnTouchPlugin)
@@ -55,14 +67,19 @@ fn main() {
         .add_plugins(ActionPlugin)
         .add_plugins(LayoutPlugin)
         .add_plugins(ScorePlugin)
+        .add_plugins(CombatLogPlugin)
         .add_plugins(HealthbarPlugin)
         .add_plugins(EnemyPlugin)
+        .add_plugins(PlayerPlugin)
+        .add_plugins(StatusPlugin)
+        .add_plugins(NotificationPlugin)
         .add_plugins(GridPlugin::new(GridConfig {
             dimensions: (5, 3),
             tile_size: vec2(64., 64.),
             movement_speed: 128.,
         }))
         .add_plugins(GamePlugin)
+        .add_plugins(EncounterPlugin)
         .add_systems(Startup, setup)
         .run();
 }
@@ -78,6 +95,7 @@ fn setup(
     commands.spawn((
         Name::new("Camera2d"),
         Camera2d,
+        SpritePickingCamera,
         Projection::Orthographic(projection)
     ));
 
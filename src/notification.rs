@@ -1,23 +1,255 @@
+use std::collections::VecDeque;
+
 use bevy::prelude::*;
 
+use crate::{damage::DamageDealt, game::GameSpeed, healthbar::Health, player::{Player, PlayerHealthLabel}};
+
+/// Lifetime of floating text.
+const FLOATING_SECS: f32 = 1.0;
+
+/// Distance floating text rises over its lifetime.
+const FLOATING_RISE: f32 = 48.;
+
+/// How long every toast is shown, including its fade out.
+const TOAST_SECS: f32 = 1.5;
+
+/// Part of the toast lifetime spent fading out.
+const TOAST_FADE_SECS: f32 = 0.4;
+
+/// Older toasts are dropped once this many are waiting.
+const MAX_QUEUED_TOASTS: usize = 5;
+
+const TOAST_POSITION: Vec3 = Vec3::new(0., 160., 50.);
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum NotificationKind {
+    Damage,
+    Crit,
+    Heal,
+    Info,
+    Warning,
+}
+
+impl NotificationKind {
+    fn color(&self) -> Color {
+        match self {
+            NotificationKind::Damage => Color::linear_rgb(1., 0.2, 0.2),
+            NotificationKind::Crit => Color::linear_rgb(1., 0.6, 0.),
+            NotificationKind::Heal => Color::linear_rgb(0.3, 1., 0.3),
+            NotificationKind::Info => Color::WHITE,
+            NotificationKind::Warning => Color::linear_rgb(1., 0.85, 0.2),
+        }
+    }
+
+    fn font_size(&self) -> f32 {
+        match self {
+            NotificationKind::Crit => 30.,
+            NotificationKind::Damage | NotificationKind::Heal => 22.,
+            NotificationKind::Info | NotificationKind::Warning => 20.,
+        }
+    }
+}
+
+/// Floating text that rises and fades at a world position.
 #[derive(Message)]
 pub struct DisplayNotification {
     pub at: Vec2,
     pub text: String,
+    pub kind: NotificationKind,
+}
+
+/// Banner shown at the top of the screen, one at a time in order.
+#[derive(Message)]
+pub struct DisplayToast {
+    pub text: String,
+    pub kind: NotificationKind,
+}
+
+#[derive(Component)]
+struct Notification {
+    timer: Timer,
+    start: Vec2,
+    color: Color,
 }
 
 #[derive(Component)]
-struct Notification(Timer);
+struct Toast(Timer);
+
+#[derive(Resource, Default)]
+struct ToastQueue(VecDeque<DisplayToast>);
+
+/// Health at the last floating number, to show the difference.
+#[derive(Component)]
+struct LastHealth(u64);
+
+pub struct NotificationPlugin;
+
+impl Plugin for NotificationPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_message::<DisplayNotification>()
+            .add_message::<DisplayToast>()
+            .init_resource::<ToastQueue>()
+            .add_systems(Update, track_health)
+            .add_systems(PostUpdate, float_health_changes)
+            .add_systems(PostUpdate, display_notifications.after(float_health_changes))
+            .add_systems(Update, animate_notifications)
+            .add_systems(Update, queue_toasts.run_if(on_message::<DisplayToast>))
+            .add_systems(Update, display_toasts.after(queue_toasts));
+    }
+}
 
-fn process_dispay_notification_messaage(
+fn track_health(
     mut commands: Commands,
-    mut reader: MessageReader<DisplayNotification>
+    query: Query<(Entity, &Health), Added<Health>>,
+) {
+    for (entity, health) in query {
+        commands.entity(entity).try_insert(LastHealth(health.0));
+    }
+}
+
+/// The player has no position of its own, its numbers float over the health label.
+fn float_health_changes(
+    query: Query<(Entity, &Health, &mut LastHealth, Option<&GlobalTransform>, Has<Player>), Changed<Health>>,
+    player_label: Option<Single<&GlobalTransform, With<PlayerHealthLabel>>>,
+    mut dealt: MessageReader<DamageDealt>,
+    mut writer: MessageWriter<DisplayNotification>,
+) {
+    let crits: Vec<Entity> = dealt
+        .read()
+        .filter(|dealt| dealt.result.is_crit)
+        .map(|dealt| dealt.target)
+        .collect();
+
+    for (entity, health, mut last, transform, is_player) in query {
+        let transform = if is_player {
+            player_label.as_ref().map(|label| ***label)
+        } else {
+            transform.copied()
+        };
+
+        let (text, kind) = match health.0.cmp(&last.0) {
+            std::cmp::Ordering::Less if crits.contains(&entity) => {
+                (format!("-{}!", last.0 - health.0), NotificationKind::Crit)
+            },
+            std::cmp::Ordering::Less => (format!("-{}", last.0 - health.0), NotificationKind::Damage),
+            std::cmp::Ordering::Greater => (format!("+{}", health.0 - last.0), NotificationKind::Heal),
+            std::cmp::Ordering::Equal => continue,
+        };
+        last.0 = health.0;
+
+        if let Some(transform) = transform {
+            writer.write(DisplayNotification {
+                at: transform.translation().truncate(),
+                text,
+                kind,
+            });
+        }
+    }
+}
+
+fn display_notifications(
+    mut commands: Commands,
+    mut reader: MessageReader<DisplayNotification>,
 ) {
     for notification in reader.read() {
+        let color = notification.kind.color();
         commands.spawn((
-            Notification(Timer::from_seconds(1.0, TimerMode::Once)),
-            Text2d::new(notification.text),
+            Name::new("Notification"),
+            Notification {
+                timer: Timer::from_seconds(FLOATING_SECS, TimerMode::Once),
+                start: notification.at,
+                color,
+            },
+            Text2d::new(notification.text.clone()),
+            TextFont {
+                font_size: notification.kind.font_size(),
+                ..default()
+            },
+            TextColor(color),
+            Transform::from_translation(notification.at.extend(50.)),
         ));
     }
 }
 
+/// Rises with an ease out and fades over the second half.
+fn animate_notifications(
+    mut commands: Commands,
+    time: Res<Time>,
+    speed: Res<GameSpeed>,
+    notifications: Query<(Entity, &mut Notification, &mut Transform, &mut TextColor)>,
+) {
+    for (entity, mut notification, mut transform, mut text_color) in notifications {
+        notification.timer.tick(time.delta().mul_f32(speed.0));
+        if notification.timer.is_finished() {
+            commands.entity(entity).despawn();
+            continue
+        }
+
+        let progress = notification.timer.fraction();
+        let rise = FLOATING_RISE * (1. - (1. - progress).powi(2));
+        transform.translation.y = notification.start.y + rise;
+        text_color.0 = notification.color.with_alpha((2. - progress * 2.).min(1.));
+    }
+}
+
+fn queue_toasts(
+    mut queue: ResMut<ToastQueue>,
+    mut reader: MessageReader<DisplayToast>,
+) {
+    for toast in reader.read() {
+        if queue.0.len() == MAX_QUEUED_TOASTS {
+            queue.0.pop_front();
+        }
+        queue.0.push_back(DisplayToast {
+            text: toast.text.clone(),
+            kind: toast.kind,
+        });
+    }
+}
+
+/// Shows the next toast once the current one is gone.
+/// Toasts are not scaled by game speed, so they stay readable at any speed.
+fn display_toasts(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut queue: ResMut<ToastQueue>,
+    mut toasts: Query<(Entity, &mut Toast, &mut Sprite, &Children)>,
+    mut texts: Query<&mut TextColor>,
+) {
+    if toasts.is_empty() {
+        if let Some(toast) = queue.0.pop_front() {
+            commands.spawn((
+                Name::new("Toast"),
+                Toast(Timer::from_seconds(TOAST_SECS, TimerMode::Once)),
+                Sprite::from_color(Color::linear_rgba(0., 0., 0., 0.8), Vec2::new(320., 40.)),
+                Transform::from_translation(TOAST_POSITION),
+                children![(
+                    Text2d::new(toast.text),
+                    TextFont {
+                        font_size: toast.kind.font_size(),
+                        ..default()
+                    },
+                    TextColor(toast.kind.color()),
+                    Transform::from_xyz(0., 0., 1.),
+                )],
+            ));
+        }
+        return
+    }
+
+    for (entity, mut toast, mut sprite, children) in &mut toasts {
+        toast.0.tick(time.delta());
+        if toast.0.is_finished() {
+            commands.entity(entity).despawn();
+            continue
+        }
+
+        let alpha = (toast.0.remaining_secs() / TOAST_FADE_SECS).min(1.);
+        sprite.color.set_alpha(alpha * 0.8);
+        let mut texts = texts.iter_many_mut(children);
+        while let Some(mut text_color) = texts.fetch_next() {
+            text_color.0.set_alpha(alpha);
+        }
+    }
+}
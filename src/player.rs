@@ -0,0 +1,96 @@
+use bevy::prelude::*;
+
+use crate::{combat_log::CombatLogEntry, healthbar::{Health, MaxHealth, Shield}, status::{StatusEffects, StatusIcons}};
+
+#[derive(Component)]
+pub struct Player;
+
+/// Displays the player's health and shield.
+#[derive(Component)]
+pub struct PlayerHealthLabel;
+
+/// Shows the player's status effects next to the health label.
+#[derive(Component)]
+pub struct PlayerStatusView;
+
+/// Damage dealt to the player by an enemy, absorbed by `Shield` first.
+/// Increased while the player is vulnerable.
+#[derive(Message)]
+pub struct DamagePlayer {
+    pub amount: u64,
+    pub source: String,
+}
+
+pub struct PlayerPlugin;
+
+impl Plugin for PlayerPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_message::<DamagePlayer>()
+            .add_systems(Startup, setup_player)
+            .add_systems(Update, damage_player.run_if(on_message::<DamagePlayer>))
+            .add_systems(Update, display_player_health.after(damage_player))
+            .add_systems(Update, attach_player_status);
+    }
+}
+
+fn setup_player(
+    mut commands: Commands,
+) {
+    commands.spawn((
+        Name::new("Player"),
+        Player,
+        Health(50),
+        MaxHealth(50),
+        Shield(0),
+        StatusEffects::default(),
+    ));
+}
+
+fn attach_player_status(
+    mut commands: Commands,
+    player: Single<Entity, With<Player>>,
+    views: Query<Entity, Added<PlayerStatusView>>,
+) {
+    for view in &views {
+        commands.entity(view).try_insert(StatusIcons {
+            owner: *player,
+        });
+    }
+}
+
+fn damage_player(
+    mut player: Single<(&mut Health, &mut Shield, &StatusEffects), With<Player>>,
+    mut reader: MessageReader<DamagePlayer>,
+    mut log: MessageWriter<CombatLogEntry>,
+) {
+    let (ref mut health, ref mut shield, effects) = *player;
+    for damage in reader.read() {
+        let total = effects.damage_taken(damage.amount);
+        let absorbed = total.min(shield.0);
+        let amount = total - absorbed;
+        shield.0 -= absorbed;
+        health.0 = health.0.saturating_sub(amount);
+
+        let mut entry = CombatLogEntry::from_enemy(&damage.source, "attack", amount)
+            .with_health(health.0);
+        if absorbed > 0 {
+            entry = entry.with_modifier(format!("shield -{}", absorbed));
+        }
+        log.write(entry);
+    }
+}
+
+fn display_player_health(
+    player: Single<(Ref<Health>, &MaxHealth, Ref<Shield>), With<Player>>,
+    labels: Query<(Ref<PlayerHealthLabel>, &mut Text2d)>,
+) {
+    let (ref health, max_health, ref shield) = *player;
+    for (label, mut text) in labels {
+        if !health.is_changed() && !shield.is_changed() && !label.is_added() {
+            continue
+        }
+
+        *text = Text2d::new(format!("hp {}/{} shield {}", health.0, max_health.0, shield.0));
+    }
+}
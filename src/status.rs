@@ -0,0 +1,266 @@
+use bevy::{platform::collections::HashMap, prelude::*};
+use serde::Deserialize;
+
+use crate::{core::prelude::*, tooltip_on_touch::TooltipOnTouch};
+
+/// Damage dealt by weakened entities is multiplied by this.
+const WEAK_MULTIPLIER: f32 = 0.75;
+
+/// Damage taken by vulnerable entities is multiplied by this.
+const VULNERABLE_MULTIPLIER: f32 = 1.5;
+
+/// Turns that burn and regen last after the last application.
+const OVER_TIME_TURNS: u64 = 3;
+
+/// Distance between the icons of a `StatusIcons` row.
+const ICON_SPACING: f32 = 28.;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Deserialize)]
+pub enum StatusEffect {
+    /// Damage equal to the stacks at the start of every turn, then loses one stack.
+    Poison,
+    /// Damage equal to the stacks at the start of every turn for a few turns.
+    Burn,
+    /// Skips the next turn, lasts one turn per stack.
+    Stun,
+    /// Deals less damage, lasts one turn per stack.
+    Weak,
+    /// Takes more damage, lasts one turn per stack.
+    Vulnerable,
+    /// Heals by the stacks at the start of every turn for a few turns.
+    Regen,
+}
+
+impl StatusEffect {
+    /// Effects over time last a fixed number of turns, the others count their stacks down.
+    fn duration(&self) -> Option<u64> {
+        match self {
+            StatusEffect::Burn | StatusEffect::Regen => Some(OVER_TIME_TURNS),
+            _ => None,
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            StatusEffect::Poison => Color::linear_rgb(0.3, 0.8, 0.1),
+            StatusEffect::Burn => Color::linear_rgb(1., 0.4, 0.),
+            StatusEffect::Stun => Color::linear_rgb(1., 0.9, 0.2),
+            StatusEffect::Weak => Color::linear_rgb(0.5, 0.5, 0.7),
+            StatusEffect::Vulnerable => Color::linear_rgb(0.8, 0.1, 0.4),
+            StatusEffect::Regen => Color::linear_rgb(0.2, 0.9, 0.6),
+        }
+    }
+
+    pub fn description(&self, stack: &StatusStack) -> String {
+        match self {
+            StatusEffect::Poison => format!("Poison: takes {} damage at the start of its turn, then decreases by 1.", stack.stacks),
+            StatusEffect::Burn => format!("Burn: takes {} damage at the start of its turn for {} turns.", stack.stacks, stack.turns),
+            StatusEffect::Stun => format!("Stun: skips its next {} turns.", stack.stacks),
+            StatusEffect::Weak => format!("Weak: deals 25% less damage for {} turns.", stack.stacks),
+            StatusEffect::Vulnerable => format!("Vulnerable: takes 50% more damage for {} turns.", stack.stacks),
+            StatusEffect::Regen => format!("Regen: heals {} at the start of its turn for {} turns.", stack.stacks, stack.turns),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct StatusStack {
+    pub stacks: u64,
+    /// Remaining turns of burn and regen, unused by the other effects.
+    pub turns: u64,
+}
+
+/// Health change caused by status effects at the start of a turn.
+#[derive(PartialEq, Debug, Default)]
+pub struct StatusTick {
+    pub damage: u64,
+    pub heal: u64,
+}
+
+/// Stacks of every status effect applied to the entity.
+#[derive(Component, Default)]
+pub struct StatusEffects {
+    pub stacks: HashMap<StatusEffect, StatusStack>,
+}
+
+impl StatusEffects {
+    /// Adds stacks, burn and regen also have their duration refreshed.
+    pub fn add(&mut self, effect: StatusEffect, stacks: u64) {
+        let stack = self.stacks.entry(effect).or_default();
+        stack.stacks += stacks;
+        if let Some(turns) = effect.duration() {
+            stack.turns = turns;
+        }
+    }
+
+    pub fn stacks(&self, effect: StatusEffect) -> u64 {
+        self.stacks.get(&effect).map(|stack| stack.stacks).unwrap_or(0)
+    }
+
+    pub fn has(&self, effect: StatusEffect) -> bool {
+        self.stacks(effect) > 0
+    }
+
+    /// Active effects in a stable order, for display.
+    pub fn sorted(&self) -> Vec<(StatusEffect, StatusStack)> {
+        let mut effects: Vec<_> = self.stacks
+            .iter()
+            .filter(|(_, stack)| stack.stacks > 0)
+            .map(|(effect, stack)| (*effect, *stack))
+            .collect();
+        effects.sort_by_key(|(effect, _)| *effect as u8);
+        effects
+    }
+
+    /// Damage dealt by the entity, reduced while it is weak.
+    pub fn damage_dealt(&self, amount: u64) -> u64 {
+        if self.has(StatusEffect::Weak) {
+            (amount as f32 * WEAK_MULTIPLIER).floor() as u64
+        } else {
+            amount
+        }
+    }
+
+    /// Damage taken by the entity, increased while it is vulnerable.
+    pub fn damage_taken(&self, amount: u64) -> u64 {
+        if self.has(StatusEffect::Vulnerable) {
+            (amount as f32 * VULNERABLE_MULTIPLIER).floor() as u64
+        } else {
+            amount
+        }
+    }
+
+    /// Damage and healing over time, poison decays afterwards.
+    pub fn start_turn(&mut self) -> StatusTick {
+        let tick = StatusTick {
+            damage: self.stacks(StatusEffect::Poison) + self.stacks(StatusEffect::Burn),
+            heal: self.stacks(StatusEffect::Regen),
+        };
+
+        if let Some(poison) = self.stacks.get_mut(&StatusEffect::Poison) {
+            poison.stacks = poison.stacks.saturating_sub(1);
+        }
+        self.remove_expired();
+        tick
+    }
+
+    /// Counts the durations down once the entity's turn is over.
+    pub fn end_turn(&mut self) {
+        for (effect, stack) in self.stacks.iter_mut() {
+            match effect {
+                StatusEffect::Poison => {},
+                StatusEffect::Burn | StatusEffect::Regen => stack.turns = stack.turns.saturating_sub(1),
+                StatusEffect::Stun | StatusEffect::Weak | StatusEffect::Vulnerable => {
+                    stack.stacks = stack.stacks.saturating_sub(1)
+                },
+            }
+        }
+        self.remove_expired();
+    }
+
+    fn remove_expired(&mut self) {
+        self.stacks.retain(|effect, stack| {
+            stack.stacks > 0 && (effect.duration().is_none() || stack.turns > 0)
+        });
+    }
+}
+
+/// Row of icons for the status effects of `owner`.
+#[derive(Component)]
+pub struct StatusIcons {
+    pub owner: Entity,
+}
+
+pub struct StatusPlugin;
+
+impl Plugin for StatusPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Update, display_status_icons);
+    }
+}
+
+/// Icons are respawned whenever the owner's effects change.
+fn display_status_icons(
+    mut commands: Commands,
+    views: Query<(Entity, Ref<StatusIcons>, Option<&Children>)>,
+    effects: Query<Ref<StatusEffects>>,
+) {
+    for (view, icons, children) in &views {
+        let Ok(effects) = effects.get(icons.owner) else {
+            continue
+        };
+
+        if !icons.is_added() && !effects.is_changed() {
+            continue
+        }
+
+        if let Some(children) = children {
+            for child in children.iter() {
+                commands.entity(child).despawn();
+            }
+        }
+
+        let sorted = effects.sorted();
+        let center = sorted.len().saturating_sub(1) as f32 / 2.;
+        commands
+            .entity(view)
+            .with_children(|view| {
+                for (i, (effect, stack)) in sorted.iter().enumerate() {
+                    view.spawn((
+                        Sprite::from_color(effect.color(), Vec2::splat(16.)),
+                        Transform::from_xyz((i as f32 - center) * ICON_SPACING, 0., 0.),
+                        TouchArea {
+                            area: Vec2::splat(20.),
+                        },
+                        TooltipOnTouch(effect.description(stack)),
+                        children![(
+                            Text2d::new(stack.stacks.to_string()),
+                            TextFont {
+                                font_size: 12.,
+                                ..default()
+                            },
+                            Transform::from_xyz(8., -8., 1.),
+                        )],
+                    ));
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_turns() {
+        let mut effects = StatusEffects::default();
+        effects.add(StatusEffect::Poison, 3);
+        effects.add(StatusEffect::Burn, 2);
+        effects.add(StatusEffect::Weak, 1);
+
+        assert_eq!(StatusTick { damage: 5, heal: 0 }, effects.start_turn());
+        assert_eq!(2, effects.stacks(StatusEffect::Poison));
+        assert_eq!(6, effects.damage_dealt(8));
+
+        effects.end_turn();
+        assert!(!effects.has(StatusEffect::Weak));
+        assert_eq!(8, effects.damage_dealt(8));
+
+        effects.end_turn();
+        effects.end_turn();
+        assert!(!effects.has(StatusEffect::Burn));
+        assert_eq!(StatusTick { damage: 2, heal: 0 }, effects.start_turn());
+        assert_eq!(StatusTick { damage: 1, heal: 0 }, effects.start_turn());
+        assert!(effects.sorted().is_empty());
+    }
+
+    #[test]
+    fn test_damage_taken() {
+        let mut effects = StatusEffects::default();
+        assert_eq!(10, effects.damage_taken(10));
+
+        effects.add(StatusEffect::Vulnerable, 2);
+        assert_eq!(15, effects.damage_taken(10));
+    }
+}